@@ -20,10 +20,20 @@ done
 for i in "${contracts[@]}"
 do
   echo -e "\nBuilding '$CONTRACTS_DIR/$i/Cargo.toml'…"
+  mkdir -p $OUT_DIR/$i
+
+  # Contracts without `#[ink::contract]` (e.g. the proxy) have no metadata, only code
+  if ! grep -q "#\[ink::contract\]" $CONTRACTS_DIR/$i/lib.rs; then
+    cargo contract build --release --quiet --generate code-only --manifest-path $CONTRACTS_DIR/$i/Cargo.toml
+
+    echo "Copying build files to '$OUT_DIR/$i/'…"
+    $CP_CMD ./target/ink/$i/$i.wasm $OUT_DIR/$i/
+    continue
+  fi
+
   cargo contract build --release --quiet --manifest-path $CONTRACTS_DIR/$i/Cargo.toml
 
   echo "Copying build files to '$OUT_DIR/$i/'…"
-  mkdir -p $OUT_DIR/$i
   $CP_CMD ./target/ink/$i/$i.contract $OUT_DIR/$i/
   $CP_CMD ./target/ink/$i/$i.wasm $OUT_DIR/$i/
   $CP_CMD ./target/ink/$i/$i.json $OUT_DIR/$i/
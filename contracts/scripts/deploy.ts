@@ -11,7 +11,8 @@ dotenv.config({
 })
 
 /**
- * Script that deploys the password manager contract and writes its address to a file.
+ * Script that deploys the password manager and version registry contracts and writes their
+ * addresses to files.
  *
  * Parameters:
  *  - `DIR`: Directory to read contract build artifacts (optional, defaults to `./deployments`)
@@ -28,11 +29,21 @@ const main = async () => {
 
   // Deploy password manager contract
   const { abi, wasm } = await getDeploymentData('keyvault')
-  const keyvault = await deployContract(api, account, abi, wasm, 'new', [account.address, 0])
+  const keyvault = await deployContract(api, account, abi, wasm, 'new', [
+    account.address,
+    { major: 0, minor: 0, patch: 0 },
+  ])
+
+  // Deploy version registry contract
+  const { abi: registryAbi, wasm: registryWasm } = await getDeploymentData('registry')
+  const registry = await deployContract(api, account, registryAbi, registryWasm, 'new', [
+    account.address,
+  ])
 
   // Write contract addresses to `{contract}/{network}.ts` file(s)
   await writeContractAddresses(chain.network, {
     keyvault,
+    registry,
   })
 }
 
@@ -2,11 +2,194 @@
 
 #[ink::contract]
 mod keyvault {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::hash::Blake2x256;
+    use ink::env::DefaultEnvironment;
+    use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
-    use ink::storage::Mapping;
+    use ink::storage::traits::ManualKey;
+    use ink::storage::{Lazy, Mapping};
+    use scale::Encode;
 
     const VERSION: u8 = 1;
 
+    /// Version of the contract's storage layout, bumped whenever code upgraded in place with
+    /// `set_code_hash` needs a one-off storage migration.
+    const STORAGE_VERSION: u16 = 1;
+
+    /// Storage key of the storage layout version, fixed so every code version finds it.
+    const STORAGE_VERSION_KEY: u32 = 0x6b76_7376;
+
+    /// Version of per-account records; accounts on an older version are upgraded when touched.
+    const ACCOUNT_VERSION: u16 = 1;
+
+    /// Storage key under which a forwarding proxy keeps the code hash it delegates calls to.
+    ///
+    /// Must match `TARGET_KEY` in the proxy contract.
+    const PROXY_TARGET_KEY: u32 = 0x6b76_7078;
+
+    /// Maximum length (in bytes) of a security advisory's message.
+    const MAX_ADVISORY_MESSAGE_LEN: usize = 280;
+
+    /// Maximum number of security advisories returned by a single `get_advisories` call.
+    const MAX_ADVISORIES_PER_QUERY: u32 = 50;
+
+    /// Maximum number of unaccepted shares an inbox holds, keeping inbox updates within gas.
+    const MAX_INBOX_SHARES: usize = 50;
+
+    /// Maximum number of unaccepted shares a single sender may have in one inbox.
+    const MAX_INBOX_SHARES_PER_SENDER: usize = 5;
+
+    /// Time (in milliseconds) between requesting to unfreeze an account and being able to.
+    const UNFREEZE_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+
+    /// Time (in milliseconds) a requested reset waits before it can be executed, unless the user
+    /// configured otherwise.
+    const DEFAULT_RESET_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+
+    /// Longest reset delay (in milliseconds) a user may configure, keeping every deadline derived
+    /// from it representable.
+    const MAX_RESET_DELAY: Timestamp = 365 * 24 * 60 * 60 * 1000;
+
+    /// Minimum time (in milliseconds) a queued admin action waits before it can be executed,
+    /// unless changed through an admin action itself.
+    const DEFAULT_ADMIN_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+
+    /// Longest admin delay (in milliseconds) the owner may set, so a typo can't leave admin actions
+    /// queued forever or overflow their deadlines.
+    const MAX_ADMIN_DELAY: Timestamp = 30 * 24 * 60 * 60 * 1000;
+
+    /// Defines an event that is emitted
+    /// when the owner proposes a new contract owner.
+    #[ink(event)]
+    pub struct ProposedOwner {
+        owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner cancels a proposed ownership transfer.
+    #[ink(event)]
+    pub struct CancelledOwnershipTransfer {
+        owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a proposed owner accepts ownership of the contract.
+    #[ink(event)]
+    pub struct TransferredOwnership {
+        previous_owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner renounces ownership of the contract.
+    #[ink(event)]
+    pub struct RenouncedOwnership {
+        previous_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a sensitive admin action is queued, warning users ahead of its execution.
+    #[ink(event)]
+    pub struct QueuedAdminAction {
+        action_id: AdminActionId,
+        action: AdminAction,
+        executable_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when a queued admin action is executed.
+    #[ink(event)]
+    pub struct ExecutedAdminAction {
+        action_id: AdminActionId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a queued admin action is cancelled.
+    #[ink(event)]
+    pub struct CancelledAdminAction {
+        action_id: AdminActionId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the contract's code is upgraded in place.
+    #[ink(event)]
+    pub struct UpgradedCode {
+        code_hash: Hash,
+    }
+
+    /// Defines an event that is emitted
+    /// when the code hash a forwarding proxy delegates calls to is updated.
+    #[ink(event)]
+    pub struct UpdatedProxyTarget {
+        code_hash: Hash,
+    }
+
+    /// Defines an event that is emitted
+    /// when the contract's storage is migrated to the current storage layout version.
+    #[ink(event)]
+    pub struct MigratedStorage {
+        from_version: u16,
+        to_version: u16,
+    }
+
+    /// Defines an event that is emitted
+    /// when an account's records are upgraded to the current account version.
+    #[ink(event)]
+    pub struct UpgradedAccount {
+        user: AccountId,
+        from_version: u16,
+        to_version: u16,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner publishes a security advisory.
+    #[ink(event)]
+    pub struct PublishedAdvisory {
+        advisory_id: AdvisoryId,
+        severity: Severity,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner retracts a security advisory.
+    #[ink(event)]
+    pub struct RetractedAdvisory {
+        advisory_id: AdvisoryId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the contract is paused.
+    #[ink(event)]
+    pub struct PausedContract {
+        by: AccountId,
+        reason: String,
+    }
+
+    /// Defines an event that is emitted
+    /// when the contract is unpaused.
+    #[ink(event)]
+    pub struct UnpausedContract {
+        by: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner grants an admin role.
+    #[ink(event)]
+    pub struct GrantedRole {
+        role: Role,
+        account: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner revokes an admin role.
+    #[ink(event)]
+    pub struct RevokedRole {
+        role: Role,
+        account: AccountId,
+    }
+
     /// Defines an event that is emitted
     /// when a user registers an account.
     #[ink(event)]
@@ -20,396 +203,5620 @@ mod keyvault {
     pub struct AddedEntry {
         user: AccountId,
         num_entries: u32,
+        device_id: Option<DeviceId>,
     }
 
-    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        /// Error for operations on an unregistered account.
-        AccountNotFound,
-        /// Error for when user attempts to create an account, but it already exists.
-        AccountAlreadyExists,
-        /// Error for when a non-owner attempts a restricted operation.
-        NotOwner,
-        /// Error for when caller attempts to create an account with insufficient payment.
-        InsufficientPayment,
-        /// Error for when an entry is added out of sequence.
-        IndexMismatch,
-        /// Error for when a transfer fails.
-        TransferFailed,
+    /// Defines an event that is emitted
+    /// when a user grants or updates a session key.
+    #[ink(event)]
+    pub struct GrantedSession {
+        user: AccountId,
+        session_key: AccountId,
+        expires_at: Timestamp,
     }
 
-    pub type Result<T> = core::result::Result<T, Error>;
+    /// Defines an event that is emitted
+    /// when a user revokes a session key.
+    #[ink(event)]
+    pub struct RevokedSession {
+        user: AccountId,
+        session_key: AccountId,
+    }
 
-    /// Represents an encrypted data entry.
-    ///
-    /// Contains encrypted data and its initialization vector (IV), essential for
-    /// secure encryption and decryption. Supports encryption schemes that require
-    /// both ciphertext and IV.
-    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
-    #[cfg_attr(
-        feature = "std",
-        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
-    )]
-    pub struct EncryptedEntry {
-        /// The encryption process's initialization vector, typically 12-16 bytes.
-        iv: Vec<u8>,
-        /// The encrypted data, secure when using a strong algorithm and secret key.
-        ciphertext: Vec<u8>,
+    /// Defines an event that is emitted
+    /// when an emergency contact requests access to a user's vault.
+    #[ink(event)]
+    pub struct RequestedEmergencyAccess {
+        user: AccountId,
+        contact: AccountId,
+        available_at: Timestamp,
     }
 
-    #[ink(storage)]
-    pub struct KeyVault {
-        /// Owner's account ID.
-        ///
-        /// The owner has exclusive rights to set certain parameters within the contract.
-        owner: AccountId,
+    /// Defines an event that is emitted
+    /// when a user denies an emergency contact's access request.
+    #[ink(event)]
+    pub struct DeniedEmergencyAccess {
+        user: AccountId,
+        contact: AccountId,
+    }
 
-        /// Fee required to create an account.
-        ///
-        /// This fee is set by the contract owner.
-        fee: Balance,
+    /// Defines an event that is emitted
+    /// when a user changes their guardians, in effect from `effective_at` on.
+    #[ink(event)]
+    pub struct ChangedGuardians {
+        user: AccountId,
+        effective_at: Timestamp,
+    }
 
-        /// Hash of user's encryption key.
-        ///
-        /// This is so that the browser extension is using the correct encryption key.
-        encryption_key_hash: Mapping<AccountId, Vec<u8>>,
+    /// Defines an event that is emitted
+    /// when a user cancels a pending guardian change.
+    #[ink(event)]
+    pub struct CancelledGuardiansChange {
+        user: AccountId,
+    }
 
-        /// Mapping from a composite key (`AccountId:index`) to an encrypted entry.
-        ///
-        /// Each entry consists of an initialization vector (IV) and ciphertext, representing encrypted data.
-        entries: Mapping<Vec<u8>, EncryptedEntry>,
+    /// Defines an event that is emitted
+    /// when a guardian approves recovering a user's vault to a new account.
+    #[ink(event)]
+    pub struct ApprovedRecovery {
+        user: AccountId,
+        guardian: AccountId,
+        new_account: AccountId,
+        approvals: u32,
+    }
 
-        /// Mapping of each account to its number of encrypted entries.
-        ///
-        /// This helps track the total entries per user.
-        num_entries: Mapping<AccountId, u32>,
+    /// Defines an event that is emitted
+    /// when a user cancels a pending recovery.
+    #[ink(event)]
+    pub struct CancelledRecovery {
+        user: AccountId,
+    }
 
-        /// Version number of the latest smart contract.
-        ///
-        /// It's used to inform users of new versions of the smart contract.
-        latest_smart_contract_version: u8,
+    /// Defines an event that is emitted
+    /// when a recovered vault has been fully moved to its new account.
+    #[ink(event)]
+    pub struct RecoveredAccount {
+        user: AccountId,
+        new_account: AccountId,
+    }
 
-        /// Optional account ID of the latest version of the smart contract.
-        ///
-        /// It's used to redirect or inform users about the most recent contract version.
-        /// A value of `None` would suggest that this contract's version is the latest.
-        latest_smart_contract_address: Option<AccountId>,
+    /// Defines an event that is emitted
+    /// when a user proposes transferring their vault to another account.
+    #[ink(event)]
+    pub struct ProposedAccountTransfer {
+        user: AccountId,
+        new_owner: AccountId,
+        executable_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when a transferred vault has been fully moved to its new owner.
+    #[ink(event)]
+    pub struct AccountTransferred {
+        user: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user's recovery code is used.
+    #[ink(event)]
+    pub struct UsedRecoveryCode {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user freezes their account.
+    #[ink(event)]
+    pub struct FrozenAccount {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user requests to unfreeze their account.
+    #[ink(event)]
+    pub struct RequestedUnfreeze {
+        user: AccountId,
+        available_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user's account is unfrozen.
+    #[ink(event)]
+    pub struct UnfrozenAccount {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user sets a new recovery code, usable from `effective_at` on.
+    #[ink(event)]
+    pub struct ChangedRecoveryCode {
+        user: AccountId,
+        effective_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user cancels a pending recovery code change.
+    #[ink(event)]
+    pub struct CancelledRecoveryCodeChange {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user requests to reset their account.
+    #[ink(event)]
+    pub struct RequestedReset {
+        user: AccountId,
+        executable_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user cancels a pending reset.
+    #[ink(event)]
+    pub struct CancelledReset {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user's account is reset.
+    #[ink(event)]
+    pub struct ResetAccount {
+        user: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user sets or rotates their public encryption key.
+    #[ink(event)]
+    pub struct SetEncryptionPubkey {
+        user: AccountId,
+        pubkey: [u8; 32],
+    }
+
+    /// Defines an event that is emitted
+    /// when a user sends a share to another user's inbox.
+    #[ink(event)]
+    pub struct SentShare {
+        sender: AccountId,
+        recipient: AccountId,
+        share_id: ShareId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user accepts a share into their vault.
+    #[ink(event)]
+    pub struct AcceptedShare {
+        user: AccountId,
+        share_id: ShareId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a recipient declines a share instead of accepting it.
+    #[ink(event)]
+    pub struct DeclinedShare {
+        user: AccountId,
+        share_id: ShareId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a sender revokes an unaccepted share.
+    #[ink(event)]
+    pub struct RevokedShare {
+        sender: AccountId,
+        recipient: AccountId,
+        share_id: ShareId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user creates a team vault.
+    #[ink(event)]
+    pub struct CreatedTeam {
+        team_id: TeamId,
+        admin: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a member is added to a team vault.
+    #[ink(event)]
+    pub struct AddedTeamMember {
+        team_id: TeamId,
+        member: AccountId,
+        role: TeamRole,
+    }
+
+    /// Defines an event that is emitted
+    /// when a member is removed from a team vault, bumping its key epoch.
+    #[ink(event)]
+    pub struct RemovedTeamMember {
+        team_id: TeamId,
+        member: AccountId,
+        key_epoch: u32,
+    }
+
+    /// Defines an event that is emitted
+    /// when a team member's role changes.
+    #[ink(event)]
+    pub struct ChangedTeamRole {
+        team_id: TeamId,
+        member: AccountId,
+        role: TeamRole,
+    }
+
+    /// Defines an event that is emitted
+    /// when a team vault's policy is set or cleared.
+    #[ink(event)]
+    pub struct SetTeamPolicy {
+        team_id: TeamId,
+        policy: Option<TeamPolicy>,
+    }
+
+    /// Defines an event that is emitted
+    /// every time an entry is added to or updated in a team vault.
+    #[ink(event)]
+    pub struct WroteTeamEntry {
+        team_id: TeamId,
+        index: u32,
+        author: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when an organization is created.
+    #[ink(event)]
+    pub struct CreatedOrg {
+        org_id: OrgId,
+        admin: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when an organization's prepaid balance is topped up.
+    #[ink(event)]
+    pub struct FundedOrg {
+        org_id: OrgId,
+        amount: Balance,
+        balance: Balance,
+    }
+
+    /// Defines an event that is emitted
+    /// when an account is designated as an organization member.
+    #[ink(event)]
+    pub struct AddedOrgMember {
+        org_id: OrgId,
+        member: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when an account stops being an organization member.
+    #[ink(event)]
+    pub struct RemovedOrgMember {
+        org_id: OrgId,
+        member: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when an organization pays a fee on behalf of a member.
+    #[ink(event)]
+    pub struct ChargedOrg {
+        org_id: OrgId,
+        member: AccountId,
+        amount: Balance,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user pushes a batch of their vault to the successor contract.
+    #[ink(event)]
+    pub struct MigratedEntries {
+        user: AccountId,
+        successor: AccountId,
+        next_index: u32,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user registers a device.
+    #[ink(event)]
+    pub struct RegisteredDevice {
+        user: AccountId,
+        device_id: DeviceId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a user revokes a device.
+    #[ink(event)]
+    pub struct RevokedDevice {
+        user: AccountId,
+        device_id: DeviceId,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Error for operations on an unregistered account.
+        AccountNotFound,
+        /// Error for when user attempts to create an account, but it already exists.
+        AccountAlreadyExists,
+        /// Error for when a non-owner attempts a restricted operation.
+        NotOwner,
+        /// Error for when someone other than the proposed owner attempts to accept ownership.
+        NotPendingOwner,
+        /// Error for when no ownership transfer has been proposed.
+        OwnershipTransferNotFound,
+        /// Error for when the caller lacks the admin role an operation requires.
+        MissingRole,
+        /// Error for operations on an admin action that isn't queued.
+        AdminActionNotFound,
+        /// Error for when a queued admin action's delay has not yet elapsed.
+        AdminActionPending,
+        /// Error for setting an admin delay longer than `MAX_ADMIN_DELAY`.
+        AdminDelayTooLong,
+        /// Error for writes while the contract is paused.
+        Paused,
+        /// Error for unpausing a contract that isn't paused.
+        NotPaused,
+        /// Error for writes after the contract's sunset deadline, naming the successor contract.
+        Sunset(Option<AccountId>),
+        /// Error for migrating when no successor contract has been published.
+        NoSuccessor,
+        /// Error for imports from a contract other than the registered predecessor.
+        NotPredecessor,
+        /// Error for when the cross-contract call to the successor fails.
+        MigrationFailed,
+        /// Error for when replacing the contract's code fails.
+        UpgradeFailed,
+        /// Error for replacing the code or installing a proxy while running behind a proxy.
+        BehindProxy,
+        /// Error for setting a proxy target while not running behind a proxy.
+        NotBehindProxy,
+        /// Error for migrating storage that is already on the current storage layout version.
+        StorageUpToDate,
+        /// Error for operations on a security advisory that doesn't exist.
+        AdvisoryNotFound,
+        /// Error for when a security advisory's message exceeds `MAX_ADVISORY_MESSAGE_LEN`.
+        AdvisoryMessageTooLong,
+        /// Error for when caller attempts to create an account with insufficient payment.
+        InsufficientPayment,
+        /// Error for when an entry is added out of sequence.
+        IndexMismatch,
+        /// Error for when a transfer fails.
+        TransferFailed,
+        /// Error for when a device id is registered twice for the same account.
+        DeviceAlreadyRegistered,
+        /// Error for operations on a device that is not registered.
+        DeviceNotFound,
+        /// Error for when a write is tagged with a revoked device.
+        DeviceRevoked,
+        /// Error for an untagged write to an account that has registered devices.
+        DeviceRequired,
+        /// Error for operations on a session key that was never granted or was revoked.
+        SessionNotFound,
+        /// Error for when a session key is used after its expiry.
+        SessionExpired,
+        /// Error for when a session key attempts an operation outside its permissions.
+        SessionNotPermitted,
+        /// Error for when a session key has used up its write quota.
+        SessionQuotaExceeded,
+        /// Error for when a session key is already in use by another account or has a vault of its own.
+        SessionKeyUnavailable,
+        /// Error for when a relayed write's signature does not match the vault owner.
+        InvalidSignature,
+        /// Error for when a relayed write uses a stale or future nonce.
+        InvalidNonce,
+        /// Error for when a relayed write is submitted after its deadline.
+        RelayExpired,
+        /// Error for operations involving an account that is not an emergency contact.
+        EmergencyContactNotFound,
+        /// Error for when an emergency contact has no open access request.
+        EmergencyAccessNotRequested,
+        /// Error for when an emergency contact's waiting period has not yet elapsed.
+        EmergencyAccessPending,
+        /// Error for when an account has no pending guardian change.
+        GuardiansChangeNotFound,
+        /// Error for when a guardian threshold is zero or exceeds the number of guardians.
+        InvalidThreshold,
+        /// Error for listing the same guardian more than once.
+        DuplicateGuardian,
+        /// Error for when a non-guardian attempts to approve a recovery.
+        NotGuardian,
+        /// Error for operations on an account without a pending recovery.
+        RecoveryNotFound,
+        /// Error for when a recovery lacks approvals or its delay has not yet elapsed.
+        RecoveryPending,
+        /// Error for writes to a vault that is being moved to another account.
+        AccountMoving,
+        /// Error for operations on an account that is not being moved.
+        AccountMoveNotFound,
+        /// Error for when no matching account transfer has been proposed.
+        AccountTransferNotFound,
+        /// Error for accepting an account transfer before the old owner's reset delay has elapsed.
+        AccountTransferPending,
+        /// Error for when an account has no recovery code set.
+        RecoveryCodeNotSet,
+        /// Error for when a presented recovery code does not match the stored hash.
+        InvalidRecoveryCode,
+        /// Error for revealing a recovery code without an earlier block's commitment to it.
+        RecoveryCodeNotCommitted,
+        /// Error for when an account has no pending recovery code change.
+        RecoveryCodeChangeNotFound,
+        /// Error for writes to a frozen account.
+        AccountFrozen,
+        /// Error for unfreeze operations on an account that is not frozen.
+        AccountNotFrozen,
+        /// Error for when an unfreeze was not requested or its delay has not yet elapsed.
+        UnfreezePending,
+        /// Error for operations on an account without a pending reset.
+        ResetNotFound,
+        /// Error for when a pending reset's delay has not yet elapsed.
+        ResetPending,
+        /// Error for setting a reset delay longer than `MAX_RESET_DELAY`.
+        ResetDelayTooLong,
+        /// Error for when an account has not published a public encryption key.
+        EncryptionPubkeyNotFound,
+        /// Error for operations on a share that doesn't exist, was accepted or was revoked.
+        ShareNotFound,
+        /// Error for sending a share to an inbox that is full, overall or for the sender.
+        InboxFull,
+        /// Error for operations on a team vault that doesn't exist.
+        TeamNotFound,
+        /// Error for operations on an account that isn't a member of the team vault.
+        NotTeamMember,
+        /// Error for when a member is added to a team vault twice.
+        TeamMemberAlreadyExists,
+        /// Error for when a team member's role doesn't allow the operation.
+        InsufficientTeamRole,
+        /// Error for when an operation would leave a team vault without an admin.
+        LastTeamAdmin,
+        /// Error for when an entry uses an encryption scheme the team vault's policy doesn't allow.
+        DisallowedScheme,
+        /// Error for when an entry's key derivation is weaker than the team vault's policy requires.
+        WeakKdf,
+        /// Error for when the writing client's version is older than supported or deprecated.
+        ClientTooOld,
+        /// Error for when the writing client's version is newer than supported.
+        ClientTooNew,
+        /// Error for operations on an organization that doesn't exist.
+        OrgNotFound,
+        /// Error for when a non-admin attempts to manage an organization.
+        NotOrgAdmin,
+        /// Error for operations on an account that isn't a member of the organization.
+        NotOrgMember,
+        /// Error for when an account is already a member of an organization.
+        OrgMemberAlreadyExists,
+        /// Error for when an organization has no free seats left.
+        OrgSeatLimitReached,
+        /// Error for when an organization's balance can't cover a fee.
+        OrgBalanceInsufficient,
+        /// Error for when a fee would exceed the organization's per-member spending cap.
+        OrgSpendingCapExceeded,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Contract-assigned identifier of a security advisory.
+    pub type AdvisoryId = u32;
+
+    /// Contract-assigned identifier of a queued admin action.
+    pub type AdminActionId = u32;
+
+    /// Client-chosen identifier of one of an account's devices.
+    pub type DeviceId = u32;
+
+    /// Contract-assigned identifier of a share sent to an inbox.
+    pub type ShareId = u64;
+
+    /// Contract-assigned identifier of a team vault.
+    pub type TeamId = u32;
+
+    /// Contract-assigned identifier of an organization.
+    pub type OrgId = u32;
+
+    /// Admin roles the owner can grant to separate contract management duties.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        /// May set the account creation fee.
+        FeeManager,
+        /// May set the latest smart contract version and address and the compatible extension version.
+        ReleaseManager,
+        /// May withdraw the contract's balance.
+        Treasurer,
+        /// May pause and unpause the contract.
+        Pauser,
+    }
+
+    impl Role {
+        /// Every role, all of which the owner holds.
+        const ALL: [Role; 4] = [
+            Role::FeeManager,
+            Role::ReleaseManager,
+            Role::Treasurer,
+            Role::Pauser,
+        ];
+    }
+
+    /// Sensitive admin changes that only take effect after the admin delay.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AdminAction {
+        /// Set the account creation fee.
+        SetAccountCreationFee(Balance),
+        /// Set the latest smart contract version.
+        SetLatestSmartContractVersion(u8),
+        /// Set the latest smart contract address.
+        SetLatestSmartContractAddress(AccountId),
+        /// Set the browser extension versions allowed to write.
+        SetExtensionCompatibility(ExtensionCompatibility),
+        /// Propose a new owner, who must then accept ownership.
+        ProposeOwner(AccountId),
+        /// Renounce ownership.
+        RenounceOwnership,
+        /// Set the delay queued admin actions wait before execution.
+        SetAdminDelay(Timestamp),
+        /// Set or clear the time after which the contract becomes read-only.
+        SetSunsetDeadline(Option<Timestamp>),
+        /// Set or clear the previous KeyVault contract allowed to import vaults.
+        SetPredecessor(Option<AccountId>),
+        /// Set or clear the version registry consulted for the latest smart contract.
+        SetRegistry(Option<AccountId>),
+        /// Replace the contract's code in place with the code at the given hash.
+        SetCodeHash(Hash),
+        /// Replace the contract's code with the forwarding proxy at the given hash, delegating
+        /// to the current code.
+        InstallProxy(Hash),
+        /// Set the KeyVault code hash a forwarding proxy delegates calls to.
+        SetProxyTarget(Hash),
+    }
+
+    impl AdminAction {
+        /// Whether only the owner may queue, cancel and so effectively perform the action.
+        fn is_owner_only(&self) -> bool {
+            matches!(
+                self,
+                AdminAction::ProposeOwner(_)
+                    | AdminAction::RenounceOwnership
+                    | AdminAction::SetAdminDelay(_)
+                    | AdminAction::SetCodeHash(_)
+                    | AdminAction::InstallProxy(_)
+                    | AdminAction::SetProxyTarget(_)
+            )
+        }
+    }
+
+    /// Represents an admin action waiting for the admin delay to elapse.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingAdminAction {
+        /// Identifier of the action.
+        action_id: AdminActionId,
+        /// The queued change.
+        action: AdminAction,
+        /// When the action may be executed.
+        executable_at: Timestamp,
+    }
+
+    /// A semantic version, ordered by major, then minor, then patch.
+    #[derive(
+        Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq, PartialOrd, Ord,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SemVer {
+        /// Incremented for incompatible changes.
+        major: u16,
+        /// Incremented for backwards-compatible features.
+        minor: u16,
+        /// Incremented for backwards-compatible fixes.
+        patch: u16,
+    }
+
+    impl SemVer {
+        /// The highest representable version, used as an open upper bound.
+        const MAX: SemVer = SemVer {
+            major: u16::MAX,
+            minor: u16::MAX,
+            patch: u16::MAX,
+        };
+    }
+
+    /// An inclusive range of semantic versions.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VersionRange {
+        /// Lowest version in the range.
+        min: SemVer,
+        /// Highest version in the range.
+        max: SemVer,
+    }
+
+    impl VersionRange {
+        /// Checks whether `version` lies within the range.
+        fn contains(&self, version: &SemVer) -> bool {
+            self.min <= *version && *version <= self.max
+        }
+    }
+
+    /// Which browser extension versions may write to this smart contract.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ExtensionCompatibility {
+        /// Versions supported, before excluding deprecated ranges.
+        supported: VersionRange,
+        /// Versions within `supported` that are no longer accepted.
+        deprecated: Vec<VersionRange>,
+    }
+
+    /// How urgently clients should act on a security advisory.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Severity {
+        Low,
+        Medium,
+        High,
+        Critical,
+    }
+
+    /// A security advisory the owner published for clients to warn users about.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Advisory {
+        /// Identifier of the advisory, assigned in publication order.
+        advisory_id: AdvisoryId,
+        /// How urgently clients should act.
+        severity: Severity,
+        /// Browser extension versions affected.
+        affected_extension_versions: Vec<VersionRange>,
+        /// Inclusive range of KeyVault smart contract versions affected.
+        affected_contract_versions: (u8, u8),
+        /// Short description for clients to display.
+        message: String,
+        /// Hash of the URL with the full advisory, so clients can verify the link they show.
+        url_hash: Hash,
+        /// When the advisory was published.
+        published_at: Timestamp,
+        /// Whether the owner has since retracted the advisory.
+        retracted: bool,
+    }
+
+    /// The latest versions for both the KeyVault smart contract and browser extension.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Versions {
+        /// Version of this smart contract.
+        current_smart_contract_version: u8,
+        /// Version of the latest smart contract.
+        latest_smart_contract_version: u8,
+        /// Address of the latest smart contract, this contract's own if it is the latest.
+        latest_smart_contract_address: AccountId,
+        /// Browser extension versions this smart contract accepts writes from.
+        extension_compatibility: ExtensionCompatibility,
+        /// Time after which this contract is read-only, if it is being sunset.
+        sunset_deadline: Option<Timestamp>,
+    }
+
+    /// Represents an encrypted data entry.
+    ///
+    /// Contains encrypted data and its initialization vector (IV), essential for
+    /// secure encryption and decryption. Supports encryption schemes that require
+    /// both ciphertext and IV.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EncryptedEntry {
+        /// The encryption process's initialization vector, typically 12-16 bytes.
+        iv: Vec<u8>,
+        /// The encrypted data, secure when using a strong algorithm and secret key.
+        ciphertext: Vec<u8>,
+        /// The device that wrote the entry, if the client tagged it.
+        device_id: Option<DeviceId>,
+        /// The account that sent the entry, if it was accepted from the inbox.
+        ///
+        /// Such entries are sealed to the recipient's public encryption key, not the vault key.
+        shared_by: Option<AccountId>,
+    }
+
+    /// Scopes a session key is allowed to act within on its delegator's vault.
+    #[derive(Debug, Clone, Copy, Default, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SessionPermissions {
+        /// Marker for read access; entries are public ciphertext, so this is informational only.
+        read: bool,
+        /// Allows appending new entries.
+        append: bool,
+        /// Marker for updating existing entries; vaults are append-only, so nothing checks it yet.
+        update: bool,
+        /// Marker for deleting entries; vaults are append-only, so nothing checks it yet.
+        delete: bool,
+    }
+
+    /// Represents a secondary `AccountId` authorized to act on another account's vault.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Session {
+        /// The account whose vault the session key acts on.
+        delegator: AccountId,
+        /// What the session key may do.
+        permissions: SessionPermissions,
+        /// Block timestamp (in milliseconds) after which the session key is no longer valid.
+        expires_at: Timestamp,
+        /// Remaining number of entries the session key may write, if limited.
+        write_quota: Option<u32>,
+    }
+
+    /// The write a vault owner signs for a relayer to submit on their behalf.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct RelayPayload {
+        /// Index the first entry is expected to land at.
+        expected_index: u32,
+        /// Entries as `(iv, ciphertext)` pairs.
+        entries: Vec<(Vec<u8>, Vec<u8>)>,
+        /// The device that produced the entries, if tagged.
+        device_id: Option<DeviceId>,
+        /// Version of the browser extension that produced the entries.
+        client_version: SemVer,
+    }
+
+    /// A signature made with an account's own key, e.g. over a relayed write.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum AccountSignature {
+        /// Signature from an sr25519 account, verified against the account ID directly.
+        Sr25519([u8; 64]),
+        /// Signature from an ecdsa account, whose ID is the blake2-256 hash of the public key.
+        Ecdsa([u8; 65]),
+    }
+
+    /// Represents a public encryption key an account published, for others to wrap secrets to.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EncryptionPubkey {
+        /// The x25519 public key.
+        pubkey: [u8; 32],
+        /// Proof of possession: an XEdDSA signature made with the x25519 private key over the
+        /// SCALE encoding of `(contract account ID, account ID, pubkey)`.
+        ///
+        /// The contract can't check XEdDSA signatures, so senders verify it before sealing
+        /// anything to the key.
+        proof: [u8; 64],
+        /// When the key was published.
+        set_at: Timestamp,
+    }
+
+    /// Represents a single credential sent to another account's inbox.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Share {
+        /// Identifier of the share.
+        share_id: ShareId,
+        /// The account that sent the share.
+        sender: AccountId,
+        /// The account the share is addressed to.
+        recipient: AccountId,
+        /// The credential, sealed to the recipient's public encryption key.
+        envelope_ciphertext: Vec<u8>,
+        /// The sender's ephemeral x25519 public key used to seal the envelope.
+        ephemeral_pubkey: [u8; 32],
+        /// When the share was sent.
+        sent_at: Timestamp,
+    }
+
+    /// What a member may do in a team vault.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum TeamRole {
+        /// May read entries.
+        Reader,
+        /// May read, add and update entries.
+        Writer,
+        /// May additionally manage members and their roles.
+        Admin,
+    }
+
+    /// Represents a vault shared by the members of a team.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Team {
+        /// Identifier of the team vault.
+        team_id: TeamId,
+        /// Current members, in the order they were added.
+        members: Vec<AccountId>,
+        /// Generation of the vault key, bumped whenever a member is removed.
+        key_epoch: u32,
+        /// Number of entries in the team vault.
+        num_entries: u32,
+    }
+
+    /// Represents a member's access to a team vault.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TeamMember {
+        /// The member's role.
+        role: TeamRole,
+        /// The team vault key, wrapped to the member's public encryption key.
+        wrapped_key: Vec<u8>,
+        /// Key epoch `wrapped_key` belongs to.
+        key_epoch: u32,
+    }
+
+    /// Requirements a team vault's admins place on every write to it.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TeamPolicy {
+        /// Minimum number of key derivation iterations.
+        min_kdf_iterations: u32,
+        /// Encryption scheme IDs entries may use; empty allows any scheme.
+        allowed_schemes: Vec<u8>,
+        /// Minimum browser extension version allowed to write.
+        min_extension_version: SemVer,
+    }
+
+    /// What the writing client reports about how an entry was encrypted.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EntryMetadata {
+        /// ID of the encryption scheme used.
+        scheme_id: u8,
+        /// Number of key derivation iterations used.
+        kdf_iterations: u32,
+        /// Version of the browser extension writing the entry.
+        extension_version: SemVer,
+    }
+
+    /// Represents an encrypted entry in a team vault.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TeamEntry {
+        /// The encryption process's initialization vector.
+        iv: Vec<u8>,
+        /// The data, encrypted with the team vault key.
+        ciphertext: Vec<u8>,
+        /// Key epoch the entry was encrypted under.
+        key_epoch: u32,
+        /// ID of the encryption scheme used.
+        scheme_id: u8,
+        /// The member that last wrote the entry.
+        author: AccountId,
+    }
+
+    /// Represents an organization that prepays fees for its members.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Org {
+        /// Identifier of the organization.
+        org_id: OrgId,
+        /// The account that manages the organization.
+        admin: AccountId,
+        /// Prepaid balance fees are paid from.
+        balance: Balance,
+        /// Maximum number of member accounts.
+        seat_limit: u32,
+        /// Current number of member accounts.
+        seats: u32,
+        /// Maximum total fees the organization pays for any single member.
+        member_spending_cap: Balance,
+    }
+
+    /// Represents an account's membership of an organization.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct OrgMembership {
+        /// The organization paying the member's fees.
+        org_id: OrgId,
+        /// Total fees the organization has paid for the member.
+        spent: Balance,
+    }
+
+    /// Represents an account designated to recover a vault if its owner is incapacitated.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EmergencyContact {
+        /// The contact's account ID.
+        contact: AccountId,
+        /// Time (in milliseconds) the owner has to deny a request before access is granted.
+        waiting_period: Timestamp,
+        /// The vault key wrapped to the contact's public key.
+        ///
+        /// Like everything in contract storage it is public, so the contact can read it before the
+        /// waiting period ends; see `get_recovery_envelope`.
+        recovery_envelope: Vec<u8>,
+        /// When the contact opened an access request, if one is open.
+        requested_at: Option<Timestamp>,
+    }
+
+    /// An account's guardians and how many of them must approve a recovery.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct GuardianConfig {
+        /// Accounts allowed to approve a recovery.
+        guardians: Vec<AccountId>,
+        /// Number of guardian approvals needed.
+        threshold: u32,
+        /// Time (in milliseconds) between reaching the threshold and the recovery becoming executable.
+        delay: Timestamp,
+    }
+
+    /// Represents a new guardian configuration waiting for the account's reset delay to elapse.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingGuardianConfig {
+        /// The new guardian configuration.
+        config: GuardianConfig,
+        /// When the new configuration takes over from the current one, if any.
+        effective_at: Timestamp,
+    }
+
+    /// Represents a proposed account transfer waiting for the account's reset delay to elapse.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct AccountTransfer {
+        /// The account the vault will be transferred to.
+        new_owner: AccountId,
+        /// When the new owner may accept the transfer.
+        executable_at: Timestamp,
+    }
+
+    /// A pending guardian recovery of an account's vault to one candidate new account.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Recovery {
+        /// The account the vault will be moved to.
+        new_account: AccountId,
+        /// Guardians that approved moving the vault to `new_account`.
+        approvals: Vec<AccountId>,
+        /// When the approval threshold was reached, if it has been.
+        approved_at: Option<Timestamp>,
+    }
+
+    /// Progress of moving a vault from one account to another.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct AccountMove {
+        /// The account the vault is moving to.
+        to: AccountId,
+        /// Index of the next entry to move.
+        next_index: u32,
+        /// Why the vault is moving.
+        kind: AccountMoveKind,
+    }
+
+    /// Reason a vault is being moved to another account.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AccountMoveKind {
+        /// Guardians recovered the vault for a lost account.
+        Recovery,
+        /// The owner transferred the vault to another of their accounts.
+        Transfer,
+    }
+
+    /// What presenting a valid recovery code should do to the account.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum RecoveryCodeAction {
+        /// Reset the account immediately, with the given encryption key hash.
+        ResetKey(Vec<u8>),
+        /// Move the vault to the given account.
+        MoveTo(AccountId),
+        /// Unfreeze the account immediately.
+        Unfreeze,
+    }
+
+    /// Represents a new recovery code waiting for its delay to elapse.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingRecoveryCode {
+        /// Hash of the new recovery code.
+        recovery_code_hash: Vec<u8>,
+        /// When the new recovery code takes over from the current one, if any.
+        effective_at: Timestamp,
+    }
+
+    /// Represents a requested account reset waiting for its delay to elapse.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingReset {
+        /// Hash of the encryption key the account will use after the reset.
+        encryption_key_hash: Vec<u8>,
+        /// When the reset may be executed.
+        executable_at: Timestamp,
+    }
+
+    /// An account's reset delay and any scheduled reduction of it.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ResetDelay {
+        /// Time (in milliseconds) a requested reset waits before it can be executed.
+        delay: Timestamp,
+        /// A shorter delay and when it takes effect, if one was requested.
+        ///
+        /// Shortening only takes effect after the current delay, so a stolen key can't skip it.
+        pending: Option<(Timestamp, Timestamp)>,
+    }
+
+    /// Represents a user-initiated freeze on all writes to an account.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Freeze {
+        /// When the account was frozen.
+        frozen_at: Timestamp,
+        /// When an unfreeze was requested, if one is pending.
+        unfreeze_requested_at: Option<Timestamp>,
+    }
+
+    /// Represents a browser extension install registered to an account.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Device {
+        /// Client-chosen identifier, unique per account.
+        device_id: DeviceId,
+        /// Human-readable label, encrypted with the user's encryption key.
+        label_ciphertext: Vec<u8>,
+        /// The device's public key.
+        pubkey: Vec<u8>,
+        /// Whether the device has been revoked and may no longer write.
+        revoked: bool,
+    }
+
+    #[ink(storage)]
+    pub struct KeyVault {
+        /// Owner's account ID.
+        ///
+        /// The owner has exclusive rights to set certain parameters within the contract.
+        owner: AccountId,
+
+        /// Account proposed as the next owner, pending its acceptance.
+        ///
+        /// Ownership only changes once this account accepts, so a typo can't brick owner-only messages.
+        pending_owner: Option<AccountId>,
+
+        /// Set of `(role, account)` pairs for admin roles granted by the owner.
+        roles: Mapping<(Role, AccountId), ()>,
+
+        /// Reason the contract was paused, if it is.
+        ///
+        /// While paused, the contract is read-only: account creation, writes and resets fail.
+        pause_reason: Option<String>,
+
+        /// Mapping of each advisory ID to its security advisory.
+        advisories: Mapping<AdvisoryId, Advisory>,
+
+        /// ID the next security advisory will be assigned.
+        next_advisory_id: AdvisoryId,
+
+        /// Admin actions queued and waiting for the admin delay to elapse.
+        pending_admin_actions: Vec<PendingAdminAction>,
+
+        /// ID the next queued admin action will be assigned.
+        next_admin_action_id: AdminActionId,
+
+        /// Time (in milliseconds) queued admin actions wait before they can be executed.
+        ///
+        /// This gives users warning before fees, versions, addresses or ownership change.
+        admin_delay: Timestamp,
+
+        /// Fee required to create an account.
+        ///
+        /// This fee is set by the contract owner.
+        fee: Balance,
+
+        /// Hash of user's encryption key.
+        ///
+        /// This is so that the browser extension is using the correct encryption key.
+        encryption_key_hash: Mapping<AccountId, Vec<u8>>,
+
+        /// Hash of user's one-time recovery code.
+        ///
+        /// Its preimage authorizes a key reset or moving the vault even if the signing key is lost.
+        recovery_code_hash: Mapping<AccountId, Vec<u8>>,
+
+        /// Mapping of each account ID to its new recovery code, if one is pending.
+        ///
+        /// A recovery code can reset, move or unfreeze the account, so setting one is time-locked.
+        pending_recovery_codes: Mapping<AccountId, PendingRecoveryCode>,
+
+        /// Mapping of an account ID and commitment to the block the recovery code use was
+        /// committed in.
+        recovery_code_commitments: Mapping<(AccountId, Hash), BlockNumber>,
+
+        /// Mapping from a composite key (`AccountId:index`) to an encrypted entry.
+        ///
+        /// Each entry consists of an initialization vector (IV) and ciphertext, representing encrypted data.
+        entries: Mapping<Vec<u8>, EncryptedEntry>,
+
+        /// Mapping of each account to its number of encrypted entries.
+        ///
+        /// This helps track the total entries per user.
+        num_entries: Mapping<AccountId, u32>,
+
+        /// Mapping of each account to its public encryption keys, oldest first.
+        ///
+        /// The last key is current; earlier ones are kept as rotation history.
+        encryption_pubkeys: Mapping<AccountId, Vec<EncryptionPubkey>>,
+
+        /// Mapping of each share ID to its unaccepted share.
+        shares: Mapping<ShareId, Share>,
+
+        /// Mapping of each account to the IDs of the unaccepted shares in its inbox.
+        inboxes: Mapping<AccountId, Vec<ShareId>>,
+
+        /// ID the next share will be assigned.
+        next_share_id: ShareId,
+
+        /// Mapping of each team ID to its team vault.
+        teams: Mapping<TeamId, Team>,
+
+        /// Mapping of each `(team ID, member)` to the member's role and wrapped vault key.
+        team_members: Mapping<(TeamId, AccountId), TeamMember>,
+
+        /// Mapping of each team ID to the policy its writes must satisfy, if any.
+        team_policies: Mapping<TeamId, TeamPolicy>,
+
+        /// Mapping of each `(team ID, index)` to a team vault entry.
+        team_entries: Mapping<(TeamId, u32), TeamEntry>,
+
+        /// ID the next team vault will be assigned.
+        next_team_id: TeamId,
+
+        /// Mapping of each organization ID to its organization.
+        orgs: Mapping<OrgId, Org>,
+
+        /// Mapping of each member account to its organization membership.
+        org_members: Mapping<AccountId, OrgMembership>,
+
+        /// ID the next organization will be assigned.
+        next_org_id: OrgId,
+
+        /// Sum of all organizations' prepaid balances.
+        ///
+        /// This part of the contract's balance belongs to organizations and can't be withdrawn by the owner.
+        total_org_balance: Balance,
+
+        /// Mapping of each account to its registered devices.
+        ///
+        /// Revoked devices are kept so that entries they wrote can still be attributed.
+        devices: Mapping<AccountId, Vec<Device>>,
+
+        /// Mapping of each frozen account to its freeze.
+        ///
+        /// Frozen accounts reject all writes and resets, so a compromised key cannot destroy data.
+        frozen: Mapping<AccountId, Freeze>,
+
+        /// Mapping of each account to its reset delay, if not the default.
+        ///
+        /// A delay of zero opts the account into instant resets.
+        reset_delays: Mapping<AccountId, ResetDelay>,
+
+        /// Mapping of each account to its pending reset.
+        pending_resets: Mapping<AccountId, PendingReset>,
+
+        /// Mapping of each session key to the session its delegator granted.
+        ///
+        /// A session key acts on the delegator's vault within the granted permissions.
+        sessions: Mapping<AccountId, Session>,
+
+        /// Mapping of each account to the nonce its next relayed write must use.
+        ///
+        /// This prevents signed write payloads from being replayed.
+        relay_nonces: Mapping<AccountId, u64>,
+
+        /// Mapping of each account to its emergency contacts.
+        ///
+        /// A contact may read its recovery envelope once a request goes undenied for the waiting period.
+        emergency_contacts: Mapping<AccountId, Vec<EmergencyContact>>,
+
+        /// Mapping of each account to its guardians.
+        guardians: Mapping<AccountId, GuardianConfig>,
+
+        /// Mapping of each account to its new guardian configuration, if one is pending.
+        ///
+        /// Guardians can move the vault, so changing them waits for the account's reset delay.
+        pending_guardians: Mapping<AccountId, PendingGuardianConfig>,
+
+        /// Mapping of each account to the guardian recoveries pending on it, one per candidate
+        /// new account.
+        ///
+        /// Each guardian backs at most one candidate, so there are never more than guardians.
+        recoveries: Mapping<AccountId, Vec<Recovery>>,
+
+        /// Mapping of each account whose vault is being moved to the progress of the move.
+        ///
+        /// Entries are moved in chunks so that large vaults fit within block limits.
+        account_moves: Mapping<AccountId, AccountMove>,
+
+        /// Mapping of each account receiving a vault to the account it is moving from.
+        ///
+        /// This reserves the destination so it cannot create its own account mid-move.
+        incoming_moves: Mapping<AccountId, AccountId>,
+
+        /// Mapping of each account to the transfer of its vault it proposed.
+        pending_transfers: Mapping<AccountId, AccountTransfer>,
+
+        /// Version number of the latest smart contract.
+        ///
+        /// It's used to inform users of new versions of the smart contract.
+        latest_smart_contract_version: u8,
+
+        /// Optional account ID of the latest version of the smart contract.
+        ///
+        /// It's used to redirect or inform users about the most recent contract version.
+        /// A value of `None` would suggest that this contract's version is the latest.
+        latest_smart_contract_address: Option<AccountId>,
+
+        /// Browser extension versions that are compatible with this smart contract.
+        ///
+        /// Writes from clients reporting a version outside this range are rejected.
+        extension_compatibility: ExtensionCompatibility,
+
+        /// Time after which this contract is read-only for all accounts, if it is being sunset.
+        ///
+        /// Users are expected to move to the latest contract before the deadline.
+        sunset_deadline: Option<Timestamp>,
+
+        /// Optional account ID of the previous version of the smart contract.
+        ///
+        /// Only this contract may import vaults through `import_from_previous`.
+        predecessor: Option<AccountId>,
+
+        /// Optional account ID of the version registry contract.
+        ///
+        /// When set, its current release takes precedence over `latest_smart_contract_version`
+        /// and `latest_smart_contract_address`. The registry only makes a release current after
+        /// its own activation delay, so users get the same warning as with the admin timelock.
+        registry: Option<AccountId>,
+
+        /// Version of the storage layout this contract's storage was last migrated to.
+        ///
+        /// Kept under its own fixed key rather than in the root storage, so code installed via
+        /// `set_code_hash` can compare it against its own `STORAGE_VERSION` and run the missing
+        /// migrations with `migrate_storage`. Storage written before versioning has none, i.e. 0.
+        storage_version: Lazy<u16, ManualKey<STORAGE_VERSION_KEY>>,
+
+        /// Mapping of each account to the version of its records.
+        ///
+        /// Accounts without an entry predate account versioning and are on version 0.
+        account_versions: Mapping<AccountId, u16>,
+    }
+
+    impl KeyVault {
+        #[ink(constructor)]
+        pub fn new(owner: AccountId, min_extension_version: SemVer) -> Self {
+            let mut roles = Mapping::new();
+            for role in Role::ALL {
+                roles.insert((role, owner), &());
+            }
+            let mut storage_version = Lazy::new();
+            storage_version.set(&STORAGE_VERSION);
+
+            Self {
+                owner,
+                pending_owner: None,
+                roles,
+                pause_reason: None,
+                advisories: Mapping::new(),
+                next_advisory_id: 0,
+                pending_admin_actions: Vec::new(),
+                next_admin_action_id: 0,
+                admin_delay: DEFAULT_ADMIN_DELAY,
+                encryption_key_hash: Mapping::new(),
+                recovery_code_hash: Mapping::new(),
+                pending_recovery_codes: Mapping::new(),
+                recovery_code_commitments: Mapping::new(),
+                entries: Mapping::new(),
+                num_entries: Mapping::new(),
+                encryption_pubkeys: Mapping::new(),
+                shares: Mapping::new(),
+                inboxes: Mapping::new(),
+                next_share_id: 0,
+                teams: Mapping::new(),
+                team_members: Mapping::new(),
+                team_policies: Mapping::new(),
+                team_entries: Mapping::new(),
+                next_team_id: 0,
+                orgs: Mapping::new(),
+                org_members: Mapping::new(),
+                next_org_id: 0,
+                total_org_balance: 0,
+                devices: Mapping::new(),
+                frozen: Mapping::new(),
+                reset_delays: Mapping::new(),
+                pending_resets: Mapping::new(),
+                sessions: Mapping::new(),
+                relay_nonces: Mapping::new(),
+                emergency_contacts: Mapping::new(),
+                guardians: Mapping::new(),
+                pending_guardians: Mapping::new(),
+                recoveries: Mapping::new(),
+                account_moves: Mapping::new(),
+                incoming_moves: Mapping::new(),
+                pending_transfers: Mapping::new(),
+                latest_smart_contract_version: VERSION,
+                latest_smart_contract_address: None,
+                extension_compatibility: ExtensionCompatibility {
+                    supported: VersionRange {
+                        min: min_extension_version,
+                        max: SemVer::MAX,
+                    },
+                    deprecated: Vec::new(),
+                },
+                sunset_deadline: None,
+                predecessor: None,
+                registry: None,
+                storage_version,
+                account_versions: Mapping::new(),
+                fee: 0,
+            }
+        }
+
+        /// Restricts a transaction to the contract owner.
+        fn is_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        /// Restricts a transaction to holders of `role`.
+        fn check_role(&self, role: Role) -> Result<()> {
+            if !self.roles.contains((role, self.env().caller())) {
+                return Err(Error::MissingRole);
+            }
+            Ok(())
+        }
+
+        /// Grants `role` to `account`, owner-restricted.
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.is_owner()?;
+            self.roles.insert((role, account), &());
+
+            // emit event
+            Self::env().emit_event(GrantedRole { role, account });
+            Ok(())
+        }
+
+        /// Revokes `role` from `account`, owner-restricted.
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.is_owner()?;
+            self.roles.remove((role, account));
+
+            // emit event
+            Self::env().emit_event(RevokedRole { role, account });
+            Ok(())
+        }
+
+        /// Moves every role from the previous owner to the new one, if any, when ownership changes.
+        ///
+        /// Roles granted to other accounts are left alone.
+        fn hand_over_roles(&mut self, previous_owner: AccountId, new_owner: Option<AccountId>) {
+            for role in Role::ALL {
+                self.roles.remove((role, previous_owner));
+                Self::env().emit_event(RevokedRole {
+                    role,
+                    account: previous_owner,
+                });
+                if let Some(new_owner) = new_owner {
+                    self.roles.insert((role, new_owner), &());
+                    Self::env().emit_event(GrantedRole {
+                        role,
+                        account: new_owner,
+                    });
+                }
+            }
+        }
+
+        /// Checks whether `account` holds `role`.
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.contains((role, account))
+        }
+
+        /// Makes sure the contract is neither paused nor past its sunset deadline.
+        fn check_not_read_only(&self) -> Result<()> {
+            if self.pause_reason.is_some() {
+                return Err(Error::Paused);
+            }
+            if let Some(deadline) = self.sunset_deadline {
+                if self.env().block_timestamp() >= deadline {
+                    return Err(Error::Sunset(self.get_successor()));
+                }
+            }
+            Ok(())
+        }
+
+        /// Pauses the contract, making it read-only until unpaused, owner- or pauser-restricted.
+        #[ink(message)]
+        pub fn pause(&mut self, reason: String) -> Result<()> {
+            self.is_owner().or_else(|_| self.check_role(Role::Pauser))?;
+            self.pause_reason = Some(reason.clone());
+
+            // emit event
+            Self::env().emit_event(PausedContract {
+                by: self.env().caller(),
+                reason,
+            });
+            Ok(())
+        }
+
+        /// Unpauses the contract, owner- or pauser-restricted.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.is_owner().or_else(|_| self.check_role(Role::Pauser))?;
+            if self.pause_reason.take().is_none() {
+                return Err(Error::NotPaused);
+            }
+
+            // emit event
+            Self::env().emit_event(UnpausedContract {
+                by: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Retrieves the reason the contract was paused, if it is.
+        #[ink(message)]
+        pub fn get_pause_reason(&self) -> Option<String> {
+            self.pause_reason.clone()
+        }
+
+        /// Restricts queueing or cancelling `action` to whoever may perform it.
+        fn check_admin_action(&self, action: &AdminAction) -> Result<()> {
+            if action.is_owner_only() {
+                return self.is_owner();
+            }
+            match action {
+                AdminAction::SetAccountCreationFee(_) => self.check_role(Role::FeeManager),
+                AdminAction::SetLatestSmartContractVersion(_)
+                | AdminAction::SetLatestSmartContractAddress(_)
+                | AdminAction::SetExtensionCompatibility(_)
+                | AdminAction::SetSunsetDeadline(_)
+                | AdminAction::SetPredecessor(_)
+                | AdminAction::SetRegistry(_) => {
+                    self.check_role(Role::ReleaseManager)
+                }
+                _ => self.is_owner(),
+            }
+        }
+
+        /// Queues `action` for execution once the admin delay has elapsed.
+        fn queue_admin_action(&mut self, action: AdminAction) -> Result<AdminActionId> {
+            self.check_admin_action(&action)?;
+
+            let action_id = self.next_admin_action_id;
+            self.next_admin_action_id = action_id.checked_add(1).expect("Overflow occurred.");
+            let executable_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(self.admin_delay)
+                .expect("Overflow occurred.");
+            self.pending_admin_actions.push(PendingAdminAction {
+                action_id,
+                action: action.clone(),
+                executable_at,
+            });
+
+            // emit event
+            Self::env().emit_event(QueuedAdminAction {
+                action_id,
+                action,
+                executable_at,
+            });
+            Ok(action_id)
+        }
+
+        /// Applies an admin action whose delay has elapsed.
+        fn apply_admin_action(&mut self, action: AdminAction) -> Result<()> {
+            match action {
+                AdminAction::SetAccountCreationFee(fee) => self.fee = fee,
+                AdminAction::SetLatestSmartContractVersion(latest) => {
+                    self.latest_smart_contract_version = latest
+                }
+                AdminAction::SetLatestSmartContractAddress(latest) => {
+                    self.latest_smart_contract_address = Some(latest)
+                }
+                AdminAction::SetExtensionCompatibility(compatibility) => {
+                    self.extension_compatibility = compatibility
+                }
+                AdminAction::ProposeOwner(new_owner) => {
+                    self.pending_owner = Some(new_owner);
+                    Self::env().emit_event(ProposedOwner {
+                        owner: self.owner,
+                        new_owner,
+                    });
+                }
+                AdminAction::RenounceOwnership => {
+                    let previous_owner = self.owner;
+                    self.owner = AccountId::from([0; 32]);
+                    self.pending_owner = None;
+                    self.hand_over_roles(previous_owner, None);
+                    // nobody may perform owner-only actions anymore, including those already queued
+                    let (dropped, kept): (Vec<_>, Vec<_>) = core::mem::take(&mut self.pending_admin_actions)
+                        .into_iter()
+                        .partition(|pending| pending.action.is_owner_only());
+                    self.pending_admin_actions = kept;
+                    for pending in dropped {
+                        Self::env().emit_event(CancelledAdminAction {
+                            action_id: pending.action_id,
+                        });
+                    }
+                    Self::env().emit_event(RenouncedOwnership { previous_owner });
+                }
+                AdminAction::SetAdminDelay(delay) => self.admin_delay = delay,
+                AdminAction::SetSunsetDeadline(deadline) => self.sunset_deadline = deadline,
+                AdminAction::SetPredecessor(predecessor) => self.predecessor = predecessor,
+                AdminAction::SetRegistry(registry) => self.registry = registry,
+                AdminAction::SetCodeHash(code_hash) => {
+                    // behind the proxy this would replace the proxy's code, uninstalling it
+                    if self.get_proxy_target().is_some() {
+                        return Err(Error::BehindProxy);
+                    }
+                    self.env()
+                        .set_code_hash(&code_hash)
+                        .map_err(|_| Error::UpgradeFailed)?;
+                    Self::env().emit_event(UpgradedCode { code_hash });
+                }
+                AdminAction::InstallProxy(proxy_code_hash) => {
+                    if self.get_proxy_target().is_some() {
+                        return Err(Error::BehindProxy);
+                    }
+                    let code_hash = self
+                        .env()
+                        .own_code_hash()
+                        .map_err(|_| Error::UpgradeFailed)?;
+                    self.env()
+                        .set_code_hash(&proxy_code_hash)
+                        .map_err(|_| Error::UpgradeFailed)?;
+                    ink::env::set_contract_storage(&PROXY_TARGET_KEY, &code_hash);
+                    Self::env().emit_event(UpgradedCode {
+                        code_hash: proxy_code_hash,
+                    });
+                    Self::env().emit_event(UpdatedProxyTarget { code_hash });
+                }
+                AdminAction::SetProxyTarget(code_hash) => {
+                    if self.get_proxy_target().is_none() {
+                        return Err(Error::NotBehindProxy);
+                    }
+                    ink::env::set_contract_storage(&PROXY_TARGET_KEY, &code_hash);
+                    Self::env().emit_event(UpdatedProxyTarget { code_hash });
+                }
+            }
+            Ok(())
+        }
+
+        /// Executes a queued admin action once its delay has elapsed.
+        #[ink(message)]
+        pub fn execute_admin_action(&mut self, action_id: AdminActionId) -> Result<()> {
+            let position = self
+                .pending_admin_actions
+                .iter()
+                .position(|pending| pending.action_id == action_id)
+                .ok_or(Error::AdminActionNotFound)?;
+            if self.env().block_timestamp() < self.pending_admin_actions[position].executable_at {
+                return Err(Error::AdminActionPending);
+            }
+
+            let pending = self.pending_admin_actions.remove(position);
+            self.apply_admin_action(pending.action)?;
+
+            // emit event
+            Self::env().emit_event(ExecutedAdminAction { action_id });
+            Ok(())
+        }
+
+        /// Cancels a queued admin action, restricted to whoever may queue it.
+        #[ink(message)]
+        pub fn cancel_admin_action(&mut self, action_id: AdminActionId) -> Result<()> {
+            let position = self
+                .pending_admin_actions
+                .iter()
+                .position(|pending| pending.action_id == action_id)
+                .ok_or(Error::AdminActionNotFound)?;
+            self.check_admin_action(&self.pending_admin_actions[position].action)?;
+            self.pending_admin_actions.remove(position);
+
+            // emit event
+            Self::env().emit_event(CancelledAdminAction { action_id });
+            Ok(())
+        }
+
+        /// Retrieves the admin actions waiting to be executed.
+        #[ink(message)]
+        pub fn get_pending_admin_actions(&self) -> Vec<PendingAdminAction> {
+            self.pending_admin_actions.clone()
+        }
+
+        /// Retrieves the delay queued admin actions wait before they can be executed.
+        #[ink(message)]
+        pub fn get_admin_delay(&self) -> Timestamp {
+            self.admin_delay
+        }
+
+        /// Queues changing the delay admin actions wait before execution, up to `MAX_ADMIN_DELAY`,
+        /// owner-restricted.
+        #[ink(message)]
+        pub fn set_admin_delay(&mut self, delay: Timestamp) -> Result<AdminActionId> {
+            if delay > MAX_ADMIN_DELAY {
+                return Err(Error::AdminDelayTooLong);
+            }
+            self.queue_admin_action(AdminAction::SetAdminDelay(delay))
+        }
+
+        /// Queues setting the address of the latest KeyVault smart contract, release manager-restricted.
+        #[ink(message)]
+        pub fn set_latest_smart_contract_address(
+            &mut self,
+            latest: AccountId,
+        ) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetLatestSmartContractAddress(latest))
+        }
+
+        /// Constructs a unique key for entry mapping from an account ID and index.
+        fn construct_key(account_id: AccountId, index: u32) -> Vec<u8> {
+            let mut key = Vec::from(account_id.as_ref() as &[u8]);
+            key.extend_from_slice(&index.to_le_bytes());
+            key
+        }
+
+        /// Creates account
+        #[ink(message, payable)]
+        pub fn create_account(
+            &mut self,
+            encryption_key_hash: Vec<u8>,
+            recovery_code_hash: Option<Vec<u8>>,
+            client_version: SemVer,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let attached_deposit = self.env().transferred_value();
+            self.check_not_read_only()?;
+            self.check_client_version(&client_version)?;
+
+            // make sure account does not already exist
+            if self.num_entries.get(caller).is_some() || self.incoming_moves.contains(caller) {
+                return Err(Error::AccountAlreadyExists);
+            // make sure the caller isn't acting as someone else's session key
+            } else if self.sessions.contains(caller) {
+                return Err(Error::SessionKeyUnavailable);
+            // organization members have the fee paid from their organization's balance, unless
+            // it can't pay, in which case they pay like everyone else
+            } else if self.charge_org(caller, self.fee).is_ok() {
+            // make sure sufficient payment is sent
+            } else if attached_deposit < self.fee {
+                return Err(Error::InsufficientPayment);
+            }
+
+            // "create" account: set hash of encryption key
+            self.encryption_key_hash
+                .insert(caller, &encryption_key_hash);
+            self.num_entries.insert(caller, &0);
+            self.account_versions.insert(caller, &ACCOUNT_VERSION);
+            // set hash of recovery code, if any
+            if let Some(recovery_code_hash) = recovery_code_hash {
+                self.recovery_code_hash.insert(caller, &recovery_code_hash);
+            }
+
+            // emit event
+            Self::env().emit_event(Registered { user: caller });
+            Ok(())
+        }
+
+        /// Sets a fresh recovery code hash for the caller's account.
+        ///
+        /// A recovery code can reset, move or unfreeze the account, so the new code only takes over
+        /// after the longer of the caller's reset delay and `UNFREEZE_DELAY`. The change can be
+        /// cancelled until then with `cancel_recovery_code_change`.
+        #[ink(message)]
+        pub fn set_recovery_code_hash(&mut self, recovery_code_hash: Vec<u8>) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            // settle a replacement whose delay has already elapsed
+            if let Some(current) = self.get_recovery_code_hash(caller) {
+                self.recovery_code_hash.insert(caller, &current);
+            }
+
+            let delay = core::cmp::max(self.get_reset_delay(caller), UNFREEZE_DELAY);
+            let effective_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(delay)
+                .expect("Overflow occurred.");
+            self.pending_recovery_codes.insert(
+                caller,
+                &PendingRecoveryCode {
+                    recovery_code_hash,
+                    effective_at,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(ChangedRecoveryCode {
+                user: caller,
+                effective_at,
+            });
+            Ok(())
+        }
+
+        /// Cancels the caller's pending recovery code change, keeping the current recovery code.
+        ///
+        /// Works on frozen accounts: the change might have been queued with the stolen key the
+        /// freeze guards against, while cancelling the owner's own change only delays it.
+        #[ink(message)]
+        pub fn cancel_recovery_code_change(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            match self.pending_recovery_codes.get(caller) {
+                Some(pending) if self.env().block_timestamp() < pending.effective_at => {
+                    self.pending_recovery_codes.remove(caller);
+                }
+                _ => return Err(Error::RecoveryCodeChangeNotFound),
+            }
+
+            // emit event
+            Self::env().emit_event(CancelledRecoveryCodeChange { user: caller });
+            Ok(())
+        }
+
+        /// Retrieves the recovery code change pending on a given account ID, if any.
+        #[ink(message)]
+        pub fn get_pending_recovery_code(
+            &self,
+            account_id: AccountId,
+        ) -> Option<PendingRecoveryCode> {
+            self.pending_recovery_codes
+                .get(account_id)
+                .filter(|pending| self.env().block_timestamp() < pending.effective_at)
+        }
+
+        /// Retrieves the hash of the recovery code currently usable for `account_id`, if any.
+        fn get_recovery_code_hash(&self, account_id: AccountId) -> Option<Vec<u8>> {
+            match self.pending_recovery_codes.get(account_id) {
+                Some(pending) if self.env().block_timestamp() >= pending.effective_at => {
+                    Some(pending.recovery_code_hash)
+                }
+                _ => self.recovery_code_hash.get(account_id),
+            }
+        }
+
+        /// Checks whether a given account ID has an unused recovery code.
+        #[ink(message)]
+        pub fn has_recovery_code(&self, account_id: AccountId) -> bool {
+            self.get_recovery_code_hash(account_id).is_some()
+        }
+
+        /// Commits to using `account_id`'s recovery code for one specific action.
+        ///
+        /// `commitment` is the blake2-256 hash of the SCALE-encoded `(code, action, account_id)`.
+        /// The code only becomes public once `use_recovery_code` reveals it in a later block, and
+        /// by then anyone trying to redirect it to another action would need a commitment of
+        /// their own, which comes too late.
+        #[ink(message)]
+        pub fn commit_recovery_code(
+            &mut self,
+            account_id: AccountId,
+            commitment: Hash,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            if !self.has_recovery_code(account_id) {
+                return Err(Error::RecoveryCodeNotSet);
+            }
+            self.recovery_code_commitments
+                .insert((account_id, commitment), &self.env().block_number());
+            Ok(())
+        }
+
+        /// Consumes `account_id`'s recovery code to reset its key, move its vault or unfreeze it.
+        ///
+        /// `code` must hash (blake2-256) to the stored recovery code hash, and `code` and `action`
+        /// must have been committed to with `commit_recovery_code` in an earlier block. The code
+        /// is single-use, so the owner must set a fresh one afterwards.
+        #[ink(message)]
+        pub fn use_recovery_code(
+            &mut self,
+            account_id: AccountId,
+            code: Vec<u8>,
+            action: RecoveryCodeAction,
+        ) -> Result<()> {
+            let recovery_code_hash = self
+                .get_recovery_code_hash(account_id)
+                .ok_or(Error::RecoveryCodeNotSet)?;
+            let code_hash = self.env().hash_bytes::<Blake2x256>(&code);
+            if code_hash.as_slice() != recovery_code_hash.as_slice() {
+                return Err(Error::InvalidRecoveryCode);
+            }
+            let commitment = Hash::from(
+                self.env()
+                    .hash_encoded::<Blake2x256, _>(&(&code, &action, account_id)),
+            );
+            match self
+                .recovery_code_commitments
+                .take((account_id, commitment))
+            {
+                Some(committed_at) if committed_at < self.env().block_number() => {}
+                _ => return Err(Error::RecoveryCodeNotCommitted),
+            }
+            if action != RecoveryCodeAction::Unfreeze {
+                self.check_writable(account_id)?;
+            }
+            self.recovery_code_hash.remove(account_id);
+            // a new code still waiting for its delay outlives the code being used up
+            if self.get_pending_recovery_code(account_id).is_none() {
+                self.pending_recovery_codes.remove(account_id);
+            }
+
+            match action {
+                RecoveryCodeAction::ResetKey(encryption_key_hash) => {
+                    self.reset(account_id, &encryption_key_hash);
+                }
+                RecoveryCodeAction::MoveTo(new_account) => {
+                    self.start_account_move(account_id, new_account, AccountMoveKind::Recovery)?;
+                }
+                RecoveryCodeAction::Unfreeze => {
+                    if self.frozen.take(account_id).is_none() {
+                        return Err(Error::AccountNotFrozen);
+                    }
+                    Self::env().emit_event(UnfrozenAccount { user: account_id });
+                }
+            }
+
+            // emit event
+            Self::env().emit_event(UsedRecoveryCode { user: account_id });
+            Ok(())
+        }
+
+        /// Retrieves the hash of the encryption key of an AccountId
+        #[ink(message)]
+        pub fn get_encryption_key_hash(&self, account_id: AccountId) -> Result<Vec<u8>> {
+            let result = self
+                .encryption_key_hash
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            Ok(result)
+        }
+
+        /// Resolves the vault the caller may append `num` entries to.
+        ///
+        /// Account holders write to their own vault; session keys write to their delegator's
+        /// vault within the granted permissions, expiry and write quota.
+        fn authorize_append(&mut self, num: u32) -> Result<AccountId> {
+            let caller = self.env().caller();
+            if self.num_entries.contains(caller) {
+                return Ok(caller);
+            }
+
+            let mut session = self.sessions.get(caller).ok_or(Error::AccountNotFound)?;
+            if self.env().block_timestamp() > session.expires_at {
+                return Err(Error::SessionExpired);
+            }
+            if !session.permissions.append {
+                return Err(Error::SessionNotPermitted);
+            }
+            if let Some(quota) = session.write_quota {
+                let remaining = quota.checked_sub(num).ok_or(Error::SessionQuotaExceeded)?;
+                session.write_quota = Some(remaining);
+                self.sessions.insert(caller, &session);
+            }
+            Ok(session.delegator)
+        }
+
+        /// Authorizes `session_key` to act on the caller's vault, replacing any previous grant.
+        #[ink(message)]
+        pub fn grant_session(
+            &mut self,
+            session_key: AccountId,
+            permissions: SessionPermissions,
+            expires_at: Timestamp,
+            write_quota: Option<u32>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_writable(caller)?;
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            // make sure the session key has no vault, isn't about to receive one and isn't
+            // delegated by someone else
+            if self.num_entries.contains(session_key) || self.incoming_moves.contains(session_key) {
+                return Err(Error::SessionKeyUnavailable);
+            }
+            if let Some(session) = self.sessions.get(session_key) {
+                if session.delegator != caller {
+                    return Err(Error::SessionKeyUnavailable);
+                }
+            }
+
+            self.sessions.insert(
+                session_key,
+                &Session {
+                    delegator: caller,
+                    permissions,
+                    expires_at,
+                    write_quota,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(GrantedSession {
+                user: caller,
+                session_key,
+                expires_at,
+            });
+            Ok(())
+        }
+
+        /// Revokes a session key previously granted by the caller.
+        ///
+        /// Works on frozen accounts, so the owner can cut off a stolen session key right away.
+        #[ink(message)]
+        pub fn revoke_session(&mut self, session_key: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            match self.sessions.get(session_key) {
+                Some(session) if session.delegator == caller => {
+                    self.sessions.remove(session_key);
+                }
+                _ => return Err(Error::SessionNotFound),
+            }
+
+            // emit event
+            Self::env().emit_event(RevokedSession {
+                user: caller,
+                session_key,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the session granted to a given session key, if any.
+        #[ink(message)]
+        pub fn get_session(&self, session_key: AccountId) -> Option<Session> {
+            self.sessions.get(session_key)
+        }
+
+        /// Adds a new encrypted entry for the caller, ensuring sequential order.
+        #[ink(message)]
+        pub fn add_entry(
+            &mut self,
+            expected_index: u32,
+            iv: Vec<u8>,
+            ciphertext: Vec<u8>,
+            device_id: Option<DeviceId>,
+            client_version: SemVer,
+        ) -> Result<()> {
+            self.check_client_version(&client_version)?;
+            let account_id = self.authorize_append(1)?;
+
+            // Check if the account exists
+            let current_index = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            if expected_index != current_index {
+                return Err(Error::IndexMismatch);
+            }
+            self.check_writable(account_id)?;
+            self.check_device(account_id, device_id)?;
+            self.migrate_account(account_id);
+
+            let key = Self::construct_key(account_id, expected_index);
+            self.entries.insert(
+                &key,
+                &EncryptedEntry {
+                    iv,
+                    ciphertext,
+                    device_id,
+                    shared_by: None,
+                },
+            );
+            let num_entries = current_index.checked_add(1).expect("Overflow occurred.");
+            self.num_entries.insert(account_id, &num_entries);
+
+            // emit event
+            Self::env().emit_event(AddedEntry {
+                user: account_id,
+                num_entries,
+                device_id,
+            });
+            Ok(())
+        }
+
+        /// Adds new encrypted entries for the caller, ensuring sequential order.
+        #[ink(message)]
+        pub fn add_entries(
+            &mut self,
+            expected_index: u32,
+            entries: Vec<(Vec<u8>, Vec<u8>)>,
+            device_id: Option<DeviceId>,
+            client_version: SemVer,
+        ) -> Result<()> {
+            self.check_client_version(&client_version)?;
+            let account_id = self.authorize_append(entries.len() as u32)?;
+            self.append_entries(account_id, expected_index, entries, device_id, None)
+        }
+
+        /// Appends entries to `account_id`'s vault once the writer has been authorized.
+        fn append_entries(
+            &mut self,
+            account_id: AccountId,
+            expected_index: u32,
+            entries: Vec<(Vec<u8>, Vec<u8>)>,
+            device_id: Option<DeviceId>,
+            shared_by: Option<AccountId>,
+        ) -> Result<()> {
+            let entries_len = entries.len() as u32;
+
+            // Check if the account exists
+            let current_index = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            if expected_index != current_index {
+                return Err(Error::IndexMismatch);
+            }
+            self.check_writable(account_id)?;
+            // shares come from another account, not one of the recipient's devices
+            if shared_by.is_none() {
+                self.check_device(account_id, device_id)?;
+            }
+            self.migrate_account(account_id);
+
+            // `entries` is assumed to be a vector of (iv, ciphertext)
+            for (i, (iv, ciphertext)) in entries.into_iter().enumerate() {
+                let idx = current_index.checked_add(i as u32).expect("Overflow occurred.");
+                let key = Self::construct_key(account_id, idx);
+                self.entries.insert(
+                    &key,
+                    &EncryptedEntry {
+                        iv,
+                        ciphertext,
+                        device_id,
+                        shared_by,
+                    },
+                );
+            }
+
+            // Update `num_entries` for the vault after all entries have been added
+            let num_entries = current_index.checked_add(entries_len).expect("Overflow occurred.");
+            self.num_entries
+                .insert(account_id, &num_entries);
+
+            // emit event
+            Self::env().emit_event(AddedEntry {
+                user: account_id,
+                num_entries,
+                device_id,
+            });
+
+            Ok(())
+        }
+
+        /// Makes sure `account_id`'s vault currently accepts writes.
+        fn check_writable(&self, account_id: AccountId) -> Result<()> {
+            self.check_not_read_only()?;
+            if self.account_moves.contains(account_id) {
+                return Err(Error::AccountMoving);
+            }
+            if self.frozen.contains(account_id) {
+                return Err(Error::AccountFrozen);
+            }
+            Ok(())
+        }
+
+        /// Freezes the caller's account, blocking all writes and resets until it is unfrozen.
+        ///
+        /// Freezing an already frozen account cancels any pending unfreeze request.
+        #[ink(message)]
+        pub fn freeze_account(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            let frozen_at = self
+                .frozen
+                .get(caller)
+                .map_or_else(|| self.env().block_timestamp(), |freeze| freeze.frozen_at);
+            self.frozen.insert(
+                caller,
+                &Freeze {
+                    frozen_at,
+                    unfreeze_requested_at: None,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(FrozenAccount { user: caller });
+            Ok(())
+        }
+
+        /// Requests to unfreeze the caller's account once `UNFREEZE_DELAY` has elapsed.
+        #[ink(message)]
+        pub fn request_unfreeze(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+            let now = self.env().block_timestamp();
+
+            let mut freeze = self.frozen.get(caller).ok_or(Error::AccountNotFrozen)?;
+            let requested_at = *freeze.unfreeze_requested_at.get_or_insert(now);
+            self.frozen.insert(caller, &freeze);
+
+            // emit event
+            Self::env().emit_event(RequestedUnfreeze {
+                user: caller,
+                available_at: requested_at
+                    .checked_add(UNFREEZE_DELAY)
+                    .expect("Overflow occurred."),
+            });
+            Ok(())
+        }
+
+        /// Unfreezes the caller's account after a requested unfreeze's delay has elapsed.
+        #[ink(message)]
+        pub fn unfreeze_account(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let freeze = self.frozen.get(caller).ok_or(Error::AccountNotFrozen)?;
+            let requested_at = freeze
+                .unfreeze_requested_at
+                .ok_or(Error::UnfreezePending)?;
+            let available_at = requested_at
+                .checked_add(UNFREEZE_DELAY)
+                .expect("Overflow occurred.");
+            if self.env().block_timestamp() < available_at {
+                return Err(Error::UnfreezePending);
+            }
+            self.frozen.remove(caller);
+
+            // emit event
+            Self::env().emit_event(UnfrozenAccount { user: caller });
+            Ok(())
+        }
+
+        /// Retrieves the freeze on a given account ID, if it is frozen.
+        #[ink(message)]
+        pub fn get_freeze(&self, account_id: AccountId) -> Option<Freeze> {
+            self.frozen.get(account_id)
+        }
+
+        /// Makes sure a write tagged with `device_id` comes from a registered, unrevoked device.
+        ///
+        /// Once an account has registered devices, even revoked ones, every write must be tagged.
+        fn check_device(&self, account_id: AccountId, device_id: Option<DeviceId>) -> Result<()> {
+            let devices = self.devices.get(account_id).unwrap_or_default();
+            let Some(device_id) = device_id else {
+                if devices.is_empty() {
+                    return Ok(());
+                }
+                return Err(Error::DeviceRequired);
+            };
+            let device = devices
+                .into_iter()
+                .find(|device| device.device_id == device_id)
+                .ok_or(Error::DeviceNotFound)?;
+            if device.revoked {
+                return Err(Error::DeviceRevoked);
+            }
+            Ok(())
+        }
+
+        /// Registers a new device for the caller's account.
+        ///
+        /// From then on, every write to the account must be tagged with a registered device ID.
+        #[ink(message)]
+        pub fn register_device(
+            &mut self,
+            device_id: DeviceId,
+            label_ciphertext: Vec<u8>,
+            device_pubkey: Vec<u8>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_writable(caller)?;
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            let mut devices = self.devices.get(caller).unwrap_or_default();
+            if devices.iter().any(|device| device.device_id == device_id) {
+                return Err(Error::DeviceAlreadyRegistered);
+            }
+            devices.push(Device {
+                device_id,
+                label_ciphertext,
+                pubkey: device_pubkey,
+                revoked: false,
+            });
+            self.devices.insert(caller, &devices);
+
+            // emit event
+            Self::env().emit_event(RegisteredDevice {
+                user: caller,
+                device_id,
+            });
+            Ok(())
+        }
+
+        /// Revokes one of the caller's devices so it can no longer write entries.
+        ///
+        /// Device IDs are reported by the client, while every device signs with the account's
+        /// key, so revocation only holds against clients that tag their writes honestly: a device
+        /// that still has the account key can claim another device's ID. Cutting off a stolen key
+        /// takes a reset or a vault move instead; session keys are revoked with `revoke_session`.
+        ///
+        /// Works on frozen accounts, so the owner can cut off a stolen device right away.
+        #[ink(message)]
+        pub fn revoke_device(&mut self, device_id: DeviceId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let mut devices = self.devices.get(caller).unwrap_or_default();
+            let device = devices
+                .iter_mut()
+                .find(|device| device.device_id == device_id)
+                .ok_or(Error::DeviceNotFound)?;
+            device.revoked = true;
+            self.devices.insert(caller, &devices);
+
+            // emit event
+            Self::env().emit_event(RevokedDevice {
+                user: caller,
+                device_id,
+            });
+            Ok(())
+        }
+
+        /// Retrieves all devices, including revoked ones, registered to a given account ID.
+        #[ink(message)]
+        pub fn list_devices(&self, account_id: AccountId) -> Vec<Device> {
+            self.devices.get(account_id).unwrap_or_default()
+        }
+
+        /// Checks that `signature` over `message` was produced by `signer`.
+        ///
+        /// sr25519 signatures are checked against the account ID as public key; ecdsa
+        /// signatures are checked by recovering the public key and hashing it into an account ID.
+        fn verify_signature(
+            &self,
+            signer: AccountId,
+            message: &[u8],
+            signature: &AccountSignature,
+        ) -> Result<()> {
+            match signature {
+                AccountSignature::Sr25519(signature) => self
+                    .env()
+                    .sr25519_verify(signature, message, signer.as_ref())
+                    .map_err(|_| Error::InvalidSignature),
+                AccountSignature::Ecdsa(signature) => {
+                    let message_hash = self.env().hash_bytes::<Blake2x256>(message);
+                    let pubkey = self
+                        .env()
+                        .ecdsa_recover(signature, &message_hash)
+                        .map_err(|_| Error::InvalidSignature)?;
+                    let recovered = AccountId::from(self.env().hash_bytes::<Blake2x256>(&pubkey));
+                    if recovered != signer {
+                        return Err(Error::InvalidSignature);
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        /// Adds entries to `owner`'s vault on their behalf, with the relayer paying fees.
+        ///
+        /// `signature` must be `owner`'s signature over the SCALE encoding of
+        /// `(contract account ID, owner, nonce, deadline, payload)`.
+        #[ink(message)]
+        pub fn relay_add_entries(
+            &mut self,
+            owner: AccountId,
+            nonce: u64,
+            deadline: Timestamp,
+            payload: RelayPayload,
+            signature: AccountSignature,
+        ) -> Result<()> {
+            if self.env().block_timestamp() > deadline {
+                return Err(Error::RelayExpired);
+            }
+            if nonce != self.relay_nonces.get(owner).unwrap_or(0) {
+                return Err(Error::InvalidNonce);
+            }
+
+            let message = (self.env().account_id(), owner, nonce, deadline, &payload).encode();
+            self.verify_signature(owner, &message, &signature)?;
+            self.check_client_version(&payload.client_version)?;
+
+            let next_nonce = nonce.checked_add(1).expect("Overflow occurred.");
+            self.relay_nonces.insert(owner, &next_nonce);
+
+            self.append_entries(
+                owner,
+                payload.expected_index,
+                payload.entries,
+                payload.device_id,
+                None,
+            )
+        }
+
+        /// Retrieves the nonce the next relayed write for a given account ID must use.
+        #[ink(message)]
+        pub fn get_relay_nonce(&self, account_id: AccountId) -> u64 {
+            self.relay_nonces.get(account_id).unwrap_or(0)
+        }
+
+        /// Designates `contact` as an emergency contact for the caller, replacing any previous
+        /// designation and closing any open request.
+        #[ink(message)]
+        pub fn set_emergency_contact(
+            &mut self,
+            contact: AccountId,
+            waiting_period: Timestamp,
+            recovery_envelope: Vec<u8>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_writable(caller)?;
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            let mut contacts = self.emergency_contacts.get(caller).unwrap_or_default();
+            contacts.retain(|c| c.contact != contact);
+            contacts.push(EmergencyContact {
+                contact,
+                waiting_period,
+                recovery_envelope,
+                requested_at: None,
+            });
+            self.emergency_contacts.insert(caller, &contacts);
+            Ok(())
+        }
+
+        /// Removes one of the caller's emergency contacts.
+        #[ink(message)]
+        pub fn remove_emergency_contact(&mut self, contact: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_writable(caller)?;
+
+            let mut contacts = self.emergency_contacts.get(caller).unwrap_or_default();
+            let len = contacts.len();
+            contacts.retain(|c| c.contact != contact);
+            if contacts.len() == len {
+                return Err(Error::EmergencyContactNotFound);
+            }
+            self.emergency_contacts.insert(caller, &contacts);
+            Ok(())
+        }
+
+        /// Opens an emergency access request on `account_id`'s vault, caller must be one of its contacts.
+        #[ink(message)]
+        pub fn request_emergency_access(&mut self, account_id: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+            let now = self.env().block_timestamp();
+
+            let mut contacts = self.emergency_contacts.get(account_id).unwrap_or_default();
+            let contact = contacts
+                .iter_mut()
+                .find(|c| c.contact == caller)
+                .ok_or(Error::EmergencyContactNotFound)?;
+            let requested_at = *contact.requested_at.get_or_insert(now);
+            let available_at = requested_at
+                .checked_add(contact.waiting_period)
+                .expect("Overflow occurred.");
+            self.emergency_contacts.insert(account_id, &contacts);
+
+            // emit event
+            Self::env().emit_event(RequestedEmergencyAccess {
+                user: account_id,
+                contact: caller,
+                available_at,
+            });
+            Ok(())
+        }
+
+        /// Denies an open emergency access request from one of the caller's contacts.
+        ///
+        /// Works on frozen accounts, since denying only keeps the vault key from the contact.
+        #[ink(message)]
+        pub fn deny_emergency_access(&mut self, contact: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let mut contacts = self.emergency_contacts.get(caller).unwrap_or_default();
+            let emergency_contact = contacts
+                .iter_mut()
+                .find(|c| c.contact == contact)
+                .ok_or(Error::EmergencyContactNotFound)?;
+            if emergency_contact.requested_at.take().is_none() {
+                return Err(Error::EmergencyAccessNotRequested);
+            }
+            self.emergency_contacts.insert(caller, &contacts);
+
+            // emit event
+            Self::env().emit_event(DeniedEmergencyAccess {
+                user: caller,
+                contact,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the caller's recovery envelope for `account_id` once the waiting period has
+        /// elapsed without the owner denying the request.
+        ///
+        /// The waiting period only gates this message, not confidentiality: the envelope sits in
+        /// public contract storage from the moment it is set, so anyone, the contact included,
+        /// can read it straight from chain state. It must be safe to publish, e.g. wrapped to a
+        /// key the contact only receives out of band once access is granted.
+        #[ink(message)]
+        pub fn get_recovery_envelope(&self, account_id: AccountId) -> Result<Vec<u8>> {
+            let caller = self.env().caller();
+
+            let contact = self
+                .emergency_contacts
+                .get(account_id)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|c| c.contact == caller)
+                .ok_or(Error::EmergencyContactNotFound)?;
+            let requested_at = contact
+                .requested_at
+                .ok_or(Error::EmergencyAccessNotRequested)?;
+            let available_at = requested_at
+                .checked_add(contact.waiting_period)
+                .expect("Overflow occurred.");
+            if self.env().block_timestamp() < available_at {
+                return Err(Error::EmergencyAccessPending);
+            }
+            Ok(contact.recovery_envelope)
+        }
+
+        /// Retrieves the emergency contacts designated by a given account ID.
+        #[ink(message)]
+        pub fn get_emergency_contacts(&self, account_id: AccountId) -> Vec<EmergencyContact> {
+            self.emergency_contacts.get(account_id).unwrap_or_default()
+        }
+
+        /// Sets the caller's guardians, approval threshold and recovery delay.
+        ///
+        /// The new configuration takes over once the caller's reset delay has elapsed, and can be
+        /// cancelled until then with `cancel_guardians_change`. Any pending recovery is discarded.
+        #[ink(message)]
+        pub fn set_guardians(
+            &mut self,
+            guardians: Vec<AccountId>,
+            threshold: u32,
+            delay: Timestamp,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            if threshold == 0 || threshold as usize > guardians.len() {
+                return Err(Error::InvalidThreshold);
+            }
+            if guardians
+                .iter()
+                .enumerate()
+                .any(|(i, guardian)| guardians[..i].contains(guardian))
+            {
+                return Err(Error::DuplicateGuardian);
+            }
+
+            // settle a change whose delay has already elapsed
+            if let Some(current) = self.get_guardians(caller) {
+                self.guardians.insert(caller, &current);
+            }
+
+            let effective_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(self.get_reset_delay(caller))
+                .expect("Overflow occurred.");
+            self.pending_guardians.insert(
+                caller,
+                &PendingGuardianConfig {
+                    config: GuardianConfig {
+                        guardians,
+                        threshold,
+                        delay,
+                    },
+                    effective_at,
+                },
+            );
+            self.recoveries.remove(caller);
+
+            // emit event
+            Self::env().emit_event(ChangedGuardians {
+                user: caller,
+                effective_at,
+            });
+            Ok(())
+        }
+
+        /// Cancels the caller's pending guardian change, keeping the current guardians.
+        ///
+        /// Works on frozen accounts: the change might have been queued with the stolen key the
+        /// freeze guards against, while cancelling the owner's own change only delays it.
+        #[ink(message)]
+        pub fn cancel_guardians_change(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            if self.get_pending_guardians(caller).is_none() {
+                return Err(Error::GuardiansChangeNotFound);
+            }
+            self.pending_guardians.remove(caller);
+
+            // emit event
+            Self::env().emit_event(CancelledGuardiansChange { user: caller });
+            Ok(())
+        }
+
+        /// Retrieves the guardians currently in effect for a given account ID.
+        #[ink(message)]
+        pub fn get_guardians(&self, account_id: AccountId) -> Option<GuardianConfig> {
+            match self.pending_guardians.get(account_id) {
+                Some(pending) if self.env().block_timestamp() >= pending.effective_at => {
+                    Some(pending.config)
+                }
+                _ => self.guardians.get(account_id),
+            }
+        }
+
+        /// Retrieves the guardian change pending on a given account ID, if any.
+        #[ink(message)]
+        pub fn get_pending_guardians(
+            &self,
+            account_id: AccountId,
+        ) -> Option<PendingGuardianConfig> {
+            self.pending_guardians
+                .get(account_id)
+                .filter(|pending| self.env().block_timestamp() < pending.effective_at)
+        }
+
+        /// Approves recovering `account_id`'s vault to `new_account`, caller must be one of its guardians.
+        ///
+        /// Approvals are counted per `new_account`, so a guardian backing another account doesn't
+        /// affect anyone else's approvals. Each guardian backs one `new_account` at a time:
+        /// approving another withdraws the guardian's earlier approval.
+        #[ink(message)]
+        pub fn recover_to(&mut self, account_id: AccountId, new_account: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let config = self.get_guardians(account_id).ok_or(Error::NotGuardian)?;
+            if !config.guardians.contains(&caller) {
+                return Err(Error::NotGuardian);
+            }
+            if self.num_entries.contains(new_account) || self.incoming_moves.contains(new_account) {
+                return Err(Error::AccountAlreadyExists);
+            }
+
+            let now = self.env().block_timestamp();
+            let mut recoveries = self.recoveries.get(account_id).unwrap_or_default();
+            if !recoveries.iter().any(|recovery| recovery.new_account == new_account) {
+                recoveries.push(Recovery {
+                    new_account,
+                    approvals: Vec::new(),
+                    approved_at: None,
+                });
+            }
+            let mut approvals = 0;
+            for recovery in recoveries.iter_mut() {
+                recovery.approvals.retain(|guardian| *guardian != caller);
+                if recovery.new_account == new_account {
+                    recovery.approvals.push(caller);
+                    approvals = recovery.approvals.len() as u32;
+                }
+                // the delay only runs while the threshold is met
+                if (recovery.approvals.len() as u32) < config.threshold {
+                    recovery.approved_at = None;
+                } else if recovery.approved_at.is_none() {
+                    recovery.approved_at = Some(now);
+                }
+            }
+            recoveries.retain(|recovery| !recovery.approvals.is_empty());
+            self.recoveries.insert(account_id, &recoveries);
+
+            // emit event
+            Self::env().emit_event(ApprovedRecovery {
+                user: account_id,
+                guardian: caller,
+                new_account,
+                approvals,
+            });
+            Ok(())
+        }
+
+        /// Cancels every recovery pending on the caller's account.
+        ///
+        /// Works on frozen accounts: cancelling only keeps the vault where it is, and at worst
+        /// delays a recovery until guardians approve it again, whereas the owner may need it to
+        /// stop a recovery started by compromised guardians.
+        #[ink(message)]
+        pub fn cancel_recovery(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            if self.recoveries.take(caller).unwrap_or_default().is_empty() {
+                return Err(Error::RecoveryNotFound);
+            }
+
+            // emit event
+            Self::env().emit_event(CancelledRecovery { user: caller });
+            Ok(())
+        }
+
+        /// Retrieves the recoveries pending on a given account ID, one per candidate new account.
+        #[ink(message)]
+        pub fn get_recoveries(&self, account_id: AccountId) -> Vec<Recovery> {
+            self.recoveries.get(account_id).unwrap_or_default()
+        }
+
+        /// Starts moving `account_id`'s vault to `new_account` once enough guardians approved
+        /// it and the delay has elapsed.
+        ///
+        /// Entries are then moved with `continue_account_move`.
+        #[ink(message)]
+        pub fn execute_recovery(
+            &mut self,
+            account_id: AccountId,
+            new_account: AccountId,
+        ) -> Result<()> {
+            let recovery = self
+                .recoveries
+                .get(account_id)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|recovery| recovery.new_account == new_account)
+                .ok_or(Error::RecoveryNotFound)?;
+            let config = self
+                .get_guardians(account_id)
+                .ok_or(Error::RecoveryNotFound)?;
+            // only approvals from guardians still in effect count
+            let approvals = recovery
+                .approvals
+                .iter()
+                .filter(|guardian| config.guardians.contains(guardian))
+                .count();
+            if approvals < config.threshold as usize {
+                return Err(Error::RecoveryPending);
+            }
+            let approved_at = recovery.approved_at.ok_or(Error::RecoveryPending)?;
+            let executable_at = approved_at
+                .checked_add(config.delay)
+                .expect("Overflow occurred.");
+            if self.env().block_timestamp() < executable_at {
+                return Err(Error::RecoveryPending);
+            }
+
+            self.recoveries.remove(account_id);
+            self.start_account_move(account_id, recovery.new_account, AccountMoveKind::Recovery)
+        }
+
+        /// Proposes transferring the caller's vault to `new_owner`, replacing any previous proposal.
+        ///
+        /// `new_owner` may accept once the caller's reset delay has elapsed, and the caller can
+        /// withdraw the proposal until then.
+        #[ink(message)]
+        pub fn propose_account_transfer(&mut self, new_owner: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            if self.num_entries.contains(new_owner) || self.incoming_moves.contains(new_owner) {
+                return Err(Error::AccountAlreadyExists);
+            }
+            let executable_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(self.get_reset_delay(caller))
+                .expect("Overflow occurred.");
+            self.pending_transfers.insert(
+                caller,
+                &AccountTransfer {
+                    new_owner,
+                    executable_at,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(ProposedAccountTransfer {
+                user: caller,
+                new_owner,
+                executable_at,
+            });
+            Ok(())
+        }
+
+        /// Withdraws the caller's pending account transfer proposal.
+        #[ink(message)]
+        pub fn cancel_account_transfer(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            if self.pending_transfers.take(caller).is_none() {
+                return Err(Error::AccountTransferNotFound);
+            }
+            Ok(())
+        }
+
+        /// Accepts `old_owner`'s proposal to transfer their vault to the caller.
+        ///
+        /// Entries are then moved with `continue_account_move`.
+        #[ink(message)]
+        pub fn accept_account_transfer(&mut self, old_owner: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+
+            let transfer = self
+                .pending_transfers
+                .get(old_owner)
+                .filter(|transfer| transfer.new_owner == caller)
+                .ok_or(Error::AccountTransferNotFound)?;
+            if self.env().block_timestamp() < transfer.executable_at {
+                return Err(Error::AccountTransferPending);
+            }
+            self.pending_transfers.remove(old_owner);
+            self.start_account_move(old_owner, caller, AccountMoveKind::Transfer)
+        }
+
+        /// Retrieves the transfer of its vault a given account ID proposed, if any.
+        #[ink(message)]
+        pub fn get_pending_account_transfer(
+            &self,
+            account_id: AccountId,
+        ) -> Option<AccountTransfer> {
+            self.pending_transfers.get(account_id)
+        }
+
+        /// Reserves `to` and blocks writes to `from` until its vault has been moved.
+        fn start_account_move(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            kind: AccountMoveKind,
+        ) -> Result<()> {
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(from)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(from)?;
+
+            if self.num_entries.contains(to) || self.incoming_moves.contains(to) {
+                return Err(Error::AccountAlreadyExists);
+            }
+            if self.sessions.contains(to) {
+                return Err(Error::SessionKeyUnavailable);
+            }
+            self.account_moves.insert(
+                from,
+                &AccountMove {
+                    to,
+                    next_index: 0,
+                    kind,
+                },
+            );
+            self.incoming_moves.insert(to, &from);
+            Ok(())
+        }
+
+        /// Moves up to `max_num` more entries of `account_id`'s vault to its new account.
+        ///
+        /// Once every entry has moved, the rest of the vault follows, the old account is closed
+        /// and `true` is returned. Works after the sunset deadline, so a move that was already
+        /// underway can finish and the vault can then be migrated with `migrate_to_latest`.
+        #[ink(message)]
+        pub fn continue_account_move(
+            &mut self,
+            account_id: AccountId,
+            max_num: u32,
+        ) -> Result<bool> {
+            let mut account_move = self
+                .account_moves
+                .get(account_id)
+                .ok_or(Error::AccountMoveNotFound)?;
+            let num = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            let last_index = self.min(
+                num,
+                account_move
+                    .next_index
+                    .checked_add(max_num)
+                    .expect("Overflow occurred."),
+            );
+            for index in account_move.next_index..last_index {
+                let key = Self::construct_key(account_id, index);
+                let entry = self.entries.take(&key).ok_or(Error::AccountNotFound)?;
+                self.entries
+                    .insert(Self::construct_key(account_move.to, index), &entry);
+            }
+            account_move.next_index = last_index;
+
+            if account_move.next_index < num {
+                self.account_moves.insert(account_id, &account_move);
+                return Ok(false);
+            }
+
+            self.finish_account_move(account_id, account_move, num);
+            Ok(true)
+        }
+
+        /// Re-homes everything but the entries from `from` to the move's destination and closes `from`.
+        fn finish_account_move(
+            &mut self,
+            from: AccountId,
+            account_move: AccountMove,
+            num_entries: u32,
+        ) {
+            let to = account_move.to;
+            if let Some(encryption_key_hash) = self.encryption_key_hash.take(from) {
+                self.encryption_key_hash.insert(to, &encryption_key_hash);
+            }
+            if let Some(recovery_code_hash) = self.recovery_code_hash.take(from) {
+                self.recovery_code_hash.insert(to, &recovery_code_hash);
+            }
+            if let Some(pending) = self.pending_recovery_codes.take(from) {
+                self.pending_recovery_codes.insert(to, &pending);
+            }
+            if let Some(reset_delay) = self.reset_delays.take(from) {
+                self.reset_delays.insert(to, &reset_delay);
+            }
+            self.pending_resets.remove(from);
+            if let Some(account_version) = self.account_versions.take(from) {
+                self.account_versions.insert(to, &account_version);
+            }
+            if let Some(devices) = self.devices.take(from) {
+                self.devices.insert(to, &devices);
+            }
+            if let Some(contacts) = self.emergency_contacts.take(from) {
+                self.emergency_contacts.insert(to, &contacts);
+            }
+            if let Some(config) = self.guardians.take(from) {
+                self.guardians.insert(to, &config);
+            }
+            if let Some(pending) = self.pending_guardians.take(from) {
+                self.pending_guardians.insert(to, &pending);
+            }
+            self.recoveries.remove(from);
+            self.pending_transfers.remove(from);
+            self.num_entries.remove(from);
+            self.num_entries.insert(to, &num_entries);
+            self.account_moves.remove(from);
+            self.incoming_moves.remove(to);
+
+            // emit event
+            match account_move.kind {
+                AccountMoveKind::Recovery => Self::env().emit_event(RecoveredAccount {
+                    user: from,
+                    new_account: to,
+                }),
+                AccountMoveKind::Transfer => Self::env().emit_event(AccountTransferred {
+                    user: from,
+                    new_owner: to,
+                }),
+            }
+        }
+
+        /// Retrieves the progress of moving a given account ID's vault, if one is underway.
+        #[ink(message)]
+        pub fn get_account_move(&self, account_id: AccountId) -> Option<AccountMove> {
+            self.account_moves.get(account_id)
+        }
+
+        /// Publishes `pubkey` as the caller's current public encryption key, along with `proof`
+        /// that the caller holds its private key.
+        ///
+        /// `proof` must be an XEdDSA signature made with the x25519 private key over the SCALE
+        /// encoding of `(contract account ID, caller, pubkey)`. It is stored for senders to verify
+        /// off-chain, as the contract can't check it, so a key whose proof doesn't verify must
+        /// not be used.
+        #[ink(message)]
+        pub fn set_encryption_pubkey(&mut self, pubkey: [u8; 32], proof: [u8; 64]) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            let mut pubkeys = self.encryption_pubkeys.get(caller).unwrap_or_default();
+            pubkeys.push(EncryptionPubkey {
+                pubkey,
+                proof,
+                set_at: self.env().block_timestamp(),
+            });
+            self.encryption_pubkeys.insert(caller, &pubkeys);
+
+            // emit event
+            Self::env().emit_event(SetEncryptionPubkey {
+                user: caller,
+                pubkey,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the current public encryption key of a given account ID.
+        #[ink(message)]
+        pub fn get_encryption_pubkey(&self, account_id: AccountId) -> Result<[u8; 32]> {
+            self.encryption_pubkeys
+                .get(account_id)
+                .unwrap_or_default()
+                .last()
+                .map(|key| key.pubkey)
+                .ok_or(Error::EncryptionPubkeyNotFound)
+        }
+
+        /// Retrieves every public encryption key a given account ID has published, oldest first.
+        #[ink(message)]
+        pub fn get_encryption_pubkey_history(
+            &self,
+            account_id: AccountId,
+        ) -> Vec<EncryptionPubkey> {
+            self.encryption_pubkeys.get(account_id).unwrap_or_default()
+        }
+
+        /// Sends a sealed credential to `recipient`'s inbox and returns its share ID.
+        ///
+        /// Only account holders may send, and an inbox holds at most `MAX_INBOX_SHARES`
+        /// unaccepted shares, `MAX_INBOX_SHARES_PER_SENDER` of them from any one sender.
+        #[ink(message)]
+        pub fn send_share(
+            &mut self,
+            recipient: AccountId,
+            envelope_ciphertext: Vec<u8>,
+            ephemeral_pubkey: [u8; 32],
+        ) -> Result<ShareId> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            // Check if both the sender's and the recipient's accounts exist
+            if !self.num_entries.contains(caller) {
+                return Err(Error::AccountNotFound);
+            }
+            let _num_entries = self
+                .num_entries
+                .get(recipient)
+                .ok_or(Error::AccountNotFound)?;
+
+            let mut inbox = self.inboxes.get(recipient).unwrap_or_default();
+            let from_caller = inbox
+                .iter()
+                .filter_map(|share_id| self.shares.get(share_id))
+                .filter(|share| share.sender == caller)
+                .count();
+            if inbox.len() >= MAX_INBOX_SHARES || from_caller >= MAX_INBOX_SHARES_PER_SENDER {
+                return Err(Error::InboxFull);
+            }
+
+            let share_id = self.next_share_id;
+            self.next_share_id = share_id.checked_add(1).expect("Overflow occurred.");
+            self.shares.insert(
+                share_id,
+                &Share {
+                    share_id,
+                    sender: caller,
+                    recipient,
+                    envelope_ciphertext,
+                    ephemeral_pubkey,
+                    sent_at: self.env().block_timestamp(),
+                },
+            );
+            inbox.push(share_id);
+            self.inboxes.insert(recipient, &inbox);
+
+            // emit event
+            Self::env().emit_event(SentShare {
+                sender: caller,
+                recipient,
+                share_id,
+            });
+            Ok(share_id)
+        }
+
+        /// Retrieves the unaccepted shares in a given account ID's inbox.
+        #[ink(message)]
+        pub fn list_inbox(&self, account_id: AccountId) -> Vec<Share> {
+            self.inboxes
+                .get(account_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|share_id| self.shares.get(share_id))
+                .collect()
+        }
+
+        /// Removes a share from storage and from its recipient's inbox.
+        fn take_share(&mut self, share: &Share) {
+            self.shares.remove(share.share_id);
+            let mut inbox = self.inboxes.get(share.recipient).unwrap_or_default();
+            inbox.retain(|share_id| *share_id != share.share_id);
+            self.inboxes.insert(share.recipient, &inbox);
+        }
+
+        /// Accepts a share from the caller's inbox, appending it to the caller's vault.
+        ///
+        /// The entry is stored as-is and marked with its sender in `shared_by`: its `ciphertext`
+        /// is the sealed envelope and its `iv` is the 32-byte ephemeral public key, so clients
+        /// open it with their encryption key rather than the vault key.
+        #[ink(message)]
+        pub fn accept_share(&mut self, share_id: ShareId) -> Result<()> {
+            let caller = self.env().caller();
+
+            let share = self
+                .shares
+                .get(share_id)
+                .filter(|share| share.recipient == caller)
+                .ok_or(Error::ShareNotFound)?;
+            let expected_index = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            self.take_share(&share);
+            self.append_entries(
+                caller,
+                expected_index,
+                Vec::from([(share.ephemeral_pubkey.to_vec(), share.envelope_ciphertext)]),
+                None,
+                Some(share.sender),
+            )?;
+
+            // emit event
+            Self::env().emit_event(AcceptedShare {
+                user: caller,
+                share_id,
+            });
+            Ok(())
+        }
+
+        /// Declines a share in the caller's inbox, e.g. to make room for others.
+        #[ink(message)]
+        pub fn decline_share(&mut self, share_id: ShareId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let share = self
+                .shares
+                .get(share_id)
+                .filter(|share| share.recipient == caller)
+                .ok_or(Error::ShareNotFound)?;
+            self.take_share(&share);
+
+            // emit event
+            Self::env().emit_event(DeclinedShare {
+                user: caller,
+                share_id,
+            });
+            Ok(())
+        }
+
+        /// Revokes a share the caller sent that hasn't been accepted yet.
+        #[ink(message)]
+        pub fn revoke_share(&mut self, share_id: ShareId) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let share = self
+                .shares
+                .get(share_id)
+                .filter(|share| share.sender == caller)
+                .ok_or(Error::ShareNotFound)?;
+            self.take_share(&share);
+
+            // emit event
+            Self::env().emit_event(RevokedShare {
+                sender: caller,
+                recipient: share.recipient,
+                share_id,
+            });
+            Ok(())
+        }
+
+        /// Creates a team vault with the caller as its admin and returns its team ID.
+        #[ink(message)]
+        pub fn create_team(&mut self, wrapped_key: Vec<u8>) -> Result<TeamId> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let team_id = self.next_team_id;
+            self.next_team_id = team_id.checked_add(1).expect("Overflow occurred.");
+            self.teams.insert(
+                team_id,
+                &Team {
+                    team_id,
+                    members: Vec::from([caller]),
+                    key_epoch: 0,
+                    num_entries: 0,
+                },
+            );
+            self.team_members.insert(
+                (team_id, caller),
+                &TeamMember {
+                    role: TeamRole::Admin,
+                    wrapped_key,
+                    key_epoch: 0,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(CreatedTeam {
+                team_id,
+                admin: caller,
+            });
+            Ok(team_id)
+        }
+
+        /// Restricts a team vault operation to members with at least `role`.
+        fn check_team_role(&self, team_id: TeamId, role: TeamRole) -> Result<Team> {
+            let team = self.teams.get(team_id).ok_or(Error::TeamNotFound)?;
+            let member = self
+                .team_members
+                .get((team_id, self.env().caller()))
+                .ok_or(Error::NotTeamMember)?;
+            if member.role < role {
+                return Err(Error::InsufficientTeamRole);
+            }
+            Ok(team)
+        }
+
+        /// Checks whether `member` is the only admin of `team`.
+        fn is_last_team_admin(&self, team: &Team, member: AccountId) -> bool {
+            !team.members.iter().any(|m| {
+                *m != member
+                    && self
+                        .team_members
+                        .get((team.team_id, *m))
+                        .is_some_and(|tm| tm.role == TeamRole::Admin)
+            })
+        }
+
+        /// Adds `member` to a team vault with `role`, admin-restricted.
+        #[ink(message)]
+        pub fn add_member(
+            &mut self,
+            team_id: TeamId,
+            member: AccountId,
+            role: TeamRole,
+            wrapped_key: Vec<u8>,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let mut team = self.check_team_role(team_id, TeamRole::Admin)?;
+            if self.team_members.contains((team_id, member)) {
+                return Err(Error::TeamMemberAlreadyExists);
+            }
+
+            team.members.push(member);
+            self.team_members.insert(
+                (team_id, member),
+                &TeamMember {
+                    role,
+                    wrapped_key,
+                    key_epoch: team.key_epoch,
+                },
+            );
+            self.teams.insert(team_id, &team);
+
+            // emit event
+            Self::env().emit_event(AddedTeamMember {
+                team_id,
+                member,
+                role,
+            });
+            Ok(())
+        }
+
+        /// Removes `member` from a team vault and bumps its key epoch, admin-restricted.
+        ///
+        /// Remaining members keep their now-stale wrapped keys until an admin re-wraps a new
+        /// vault key for them with `set_wrapped_key`.
+        #[ink(message)]
+        pub fn remove_member(&mut self, team_id: TeamId, member: AccountId) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let mut team = self.check_team_role(team_id, TeamRole::Admin)?;
+            if !self.team_members.contains((team_id, member)) {
+                return Err(Error::NotTeamMember);
+            }
+            if self.is_last_team_admin(&team, member) {
+                return Err(Error::LastTeamAdmin);
+            }
+
+            team.members.retain(|m| *m != member);
+            team.key_epoch = team.key_epoch.checked_add(1).expect("Overflow occurred.");
+            self.team_members.remove((team_id, member));
+            self.teams.insert(team_id, &team);
+
+            // emit event
+            Self::env().emit_event(RemovedTeamMember {
+                team_id,
+                member,
+                key_epoch: team.key_epoch,
+            });
+            Ok(())
+        }
+
+        /// Changes `member`'s role in a team vault, admin-restricted.
+        #[ink(message)]
+        pub fn set_role(
+            &mut self,
+            team_id: TeamId,
+            member: AccountId,
+            role: TeamRole,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let team = self.check_team_role(team_id, TeamRole::Admin)?;
+            let mut team_member = self
+                .team_members
+                .get((team_id, member))
+                .ok_or(Error::NotTeamMember)?;
+            if role != TeamRole::Admin && self.is_last_team_admin(&team, member) {
+                return Err(Error::LastTeamAdmin);
+            }
+
+            team_member.role = role;
+            self.team_members.insert((team_id, member), &team_member);
+
+            // emit event
+            Self::env().emit_event(ChangedTeamRole {
+                team_id,
+                member,
+                role,
+            });
+            Ok(())
+        }
+
+        /// Sets `member`'s vault key wrapped for the team vault's current key epoch, admin-restricted.
+        #[ink(message)]
+        pub fn set_wrapped_key(
+            &mut self,
+            team_id: TeamId,
+            member: AccountId,
+            wrapped_key: Vec<u8>,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let team = self.check_team_role(team_id, TeamRole::Admin)?;
+            let mut team_member = self
+                .team_members
+                .get((team_id, member))
+                .ok_or(Error::NotTeamMember)?;
+
+            team_member.wrapped_key = wrapped_key;
+            team_member.key_epoch = team.key_epoch;
+            self.team_members.insert((team_id, member), &team_member);
+            Ok(())
+        }
+
+        /// Retrieves a team vault.
+        #[ink(message)]
+        pub fn get_team(&self, team_id: TeamId) -> Result<Team> {
+            self.teams.get(team_id).ok_or(Error::TeamNotFound)
+        }
+
+        /// Retrieves a member's role and wrapped vault key in a team vault.
+        #[ink(message)]
+        pub fn get_team_member(&self, team_id: TeamId, member: AccountId) -> Result<TeamMember> {
+            self.team_members
+                .get((team_id, member))
+                .ok_or(Error::NotTeamMember)
+        }
+
+        /// Makes sure a write to a team vault comes from a supported client and satisfies the
+        /// team's policy, if it has one.
+        fn check_team_policy(&self, team_id: TeamId, metadata: &EntryMetadata) -> Result<()> {
+            self.check_client_version(&metadata.extension_version)?;
+            let Some(policy) = self.team_policies.get(team_id) else {
+                return Ok(());
+            };
+            if !policy.allowed_schemes.is_empty()
+                && !policy.allowed_schemes.contains(&metadata.scheme_id)
+            {
+                return Err(Error::DisallowedScheme);
+            }
+            if metadata.kdf_iterations < policy.min_kdf_iterations {
+                return Err(Error::WeakKdf);
+            }
+            if metadata.extension_version < policy.min_extension_version {
+                return Err(Error::ClientTooOld);
+            }
+            Ok(())
+        }
+
+        /// Sets or clears the policy writes to a team vault must satisfy, admin-restricted.
+        #[ink(message)]
+        pub fn set_team_policy(
+            &mut self,
+            team_id: TeamId,
+            policy: Option<TeamPolicy>,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            self.check_team_role(team_id, TeamRole::Admin)?;
+            if let Some(policy) = &policy {
+                self.team_policies.insert(team_id, policy);
+            } else {
+                self.team_policies.remove(team_id);
+            }
+
+            // emit event
+            Self::env().emit_event(SetTeamPolicy { team_id, policy });
+            Ok(())
+        }
+
+        /// Retrieves the policy of a team vault, if it has one.
+        #[ink(message)]
+        pub fn get_team_policy(&self, team_id: TeamId) -> Option<TeamPolicy> {
+            self.team_policies.get(team_id)
+        }
+
+        /// Adds a new encrypted entry to a team vault, ensuring sequential order, writer-restricted.
+        #[ink(message)]
+        pub fn add_team_entry(
+            &mut self,
+            team_id: TeamId,
+            expected_index: u32,
+            iv: Vec<u8>,
+            ciphertext: Vec<u8>,
+            metadata: EntryMetadata,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+            let mut team = self.check_team_role(team_id, TeamRole::Writer)?;
+            if expected_index != team.num_entries {
+                return Err(Error::IndexMismatch);
+            }
+            self.check_team_policy(team_id, &metadata)?;
+
+            let author = self.env().caller();
+            self.team_entries.insert(
+                (team_id, expected_index),
+                &TeamEntry {
+                    iv,
+                    ciphertext,
+                    key_epoch: team.key_epoch,
+                    scheme_id: metadata.scheme_id,
+                    author,
+                },
+            );
+            team.num_entries = expected_index.checked_add(1).expect("Overflow occurred.");
+            self.teams.insert(team_id, &team);
+
+            // emit event
+            Self::env().emit_event(WroteTeamEntry {
+                team_id,
+                index: expected_index,
+                author,
+            });
+            Ok(())
+        }
+
+        /// Replaces an existing entry in a team vault, e.g. after a key rotation, writer-restricted.
+        #[ink(message)]
+        pub fn update_team_entry(
+            &mut self,
+            team_id: TeamId,
+            index: u32,
+            iv: Vec<u8>,
+            ciphertext: Vec<u8>,
+            metadata: EntryMetadata,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+            let team = self.check_team_role(team_id, TeamRole::Writer)?;
+            if index >= team.num_entries {
+                return Err(Error::IndexMismatch);
+            }
+            self.check_team_policy(team_id, &metadata)?;
+
+            let author = self.env().caller();
+            self.team_entries.insert(
+                (team_id, index),
+                &TeamEntry {
+                    iv,
+                    ciphertext,
+                    key_epoch: team.key_epoch,
+                    scheme_id: metadata.scheme_id,
+                    author,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(WroteTeamEntry {
+                team_id,
+                index,
+                author,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the encrypted entries requested from a team vault.
+        #[ink(message)]
+        pub fn get_team_entries(
+            &self,
+            team_id: TeamId,
+            start_index: u32,
+            max_num: u32,
+        ) -> Result<Vec<TeamEntry>> {
+            let team = self.teams.get(team_id).ok_or(Error::TeamNotFound)?;
+            if start_index >= team.num_entries {
+                return Err(Error::IndexMismatch);
+            }
+
+            let mut results = Vec::new();
+            let last_index = start_index.checked_add(max_num).expect("Overflow occurred.");
+            for index in start_index..self.min(team.num_entries, last_index) {
+                let entry = self
+                    .team_entries
+                    .get((team_id, index))
+                    .ok_or(Error::TeamNotFound)?;
+                results.push(entry);
+            }
+
+            Ok(results)
+        }
+
+        /// Retrieves the number of entries for a given account ID.
+        #[ink(message)]
+        pub fn get_entry_count(&self, account_id: AccountId) -> Result<u32> {
+            self.num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)
+        }
+
+        /// Retrieves an encrypted entry by index for a given account ID.
+        #[ink(message)]
+        pub fn get_entry(&self, account_id: AccountId, index: u32) -> Result<EncryptedEntry> {
+            let num = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if index >= num {
+                return Err(Error::IndexMismatch);
+            }
+
+            let key = Self::construct_key(account_id, index);
+            self.entries.get(key).ok_or(Error::AccountNotFound)
+        }
+
+        fn min(&self, a: u32, b: u32) -> u32 {
+            if a <= b {
+                a
+            } else {
+                b
+            }
+        }
+
+        /// Retrieves the encrypted entries requested for a given account ID.
+        #[ink(message)]
+        pub fn get_entries(
+            &self,
+            account_id: AccountId,
+            start_index: u32,
+            max_num: u32,
+        ) -> Result<Vec<EncryptedEntry>> {
+            let num = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if start_index >= num {
+                return Err(Error::IndexMismatch);
+            }
+
+            let mut results = Vec::new();
+            let last_index = start_index.checked_add(max_num).expect("Overflow occurred.");
+            for index in start_index..self.min(num, last_index) {
+                let key = Self::construct_key(account_id, index);
+                let entry = self.entries.get(&key).ok_or(Error::AccountNotFound)?;
+                results.push(entry);
+            }
+
+            Ok(results)
+        }
+
+        /// Pushes up to `max_num` of the caller's entries, starting at `start_index`, to the
+        /// latest contract, as reported by `get_versions`, and returns the index to resume from.
+        ///
+        /// The first batch (`start_index == 0`) also creates the account on the successor,
+        /// carrying over the encryption key hash. Works after the sunset deadline, since it only
+        /// reads from this contract.
+        #[ink(message)]
+        pub fn migrate_to_latest(&mut self, start_index: u32, max_num: u32) -> Result<u32> {
+            let caller = self.env().caller();
+
+            let successor = self.get_successor().ok_or(Error::NoSuccessor)?;
+            let num = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            if start_index > num {
+                return Err(Error::IndexMismatch);
+            }
+            let encryption_key_hash = self
+                .encryption_key_hash
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+
+            let mut entries = Vec::new();
+            let last_index = self.min(
+                num,
+                start_index.checked_add(max_num).expect("Overflow occurred."),
+            );
+            for index in start_index..last_index {
+                let key = Self::construct_key(caller, index);
+                let entry = self.entries.get(&key).ok_or(Error::AccountNotFound)?;
+                entries.push(entry);
+            }
+
+            let result = build_call::<DefaultEnvironment>()
+                .call(successor)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                        "import_from_previous"
+                    )))
+                    .push_arg(caller)
+                    .push_arg(&encryption_key_hash)
+                    .push_arg(start_index)
+                    .push_arg(&entries),
+                )
+                .returns::<Result<()>>()
+                .try_invoke();
+            match result {
+                // propagate errors returned by the successor itself, e.g. `IndexMismatch`
+                Ok(Ok(result)) => result?,
+                _ => return Err(Error::MigrationFailed),
+            }
+
+            // emit event
+            Self::env().emit_event(MigratedEntries {
+                user: caller,
+                successor,
+                next_index: last_index,
+            });
+            Ok(last_index)
+        }
+
+        /// Imports a batch of `account_id`'s vault, restricted to the predecessor contract.
+        ///
+        /// The account is created with `encryption_key_hash` when the first batch arrives;
+        /// batches must arrive in order.
+        #[ink(message)]
+        pub fn import_from_previous(
+            &mut self,
+            account_id: AccountId,
+            encryption_key_hash: Vec<u8>,
+            expected_index: u32,
+            entries: Vec<EncryptedEntry>,
+        ) -> Result<()> {
+            if self.predecessor != Some(self.env().caller()) {
+                return Err(Error::NotPredecessor);
+            }
+
+            let current_index = match self.num_entries.get(account_id) {
+                Some(num) => num,
+                None if expected_index == 0 && !self.incoming_moves.contains(account_id) => {
+                    self.check_not_read_only()?;
+                    self.encryption_key_hash
+                        .insert(account_id, &encryption_key_hash);
+                    self.num_entries.insert(account_id, &0);
+                    self.account_versions.insert(account_id, &ACCOUNT_VERSION);
+                    Self::env().emit_event(Registered { user: account_id });
+                    0
+                }
+                None => return Err(Error::AccountNotFound),
+            };
+            if expected_index != current_index {
+                return Err(Error::IndexMismatch);
+            }
+            self.check_writable(account_id)?;
+            self.migrate_account(account_id);
+
+            let entries_len = entries.len() as u32;
+            for (i, entry) in entries.into_iter().enumerate() {
+                let idx = current_index.checked_add(i as u32).expect("Overflow occurred.");
+                let key = Self::construct_key(account_id, idx);
+                self.entries.insert(&key, &entry);
+            }
+
+            let num_entries = current_index.checked_add(entries_len).expect("Overflow occurred.");
+            self.num_entries.insert(account_id, &num_entries);
+
+            // emit event
+            Self::env().emit_event(AddedEntry {
+                user: account_id,
+                num_entries,
+                device_id: None,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the previous KeyVault smart contract allowed to import vaults, if any.
+        #[ink(message)]
+        pub fn get_predecessor(&self) -> Option<AccountId> {
+            self.predecessor
+        }
+
+        /// Queues setting or clearing the predecessor contract, release manager-restricted.
+        #[ink(message)]
+        pub fn set_predecessor(&mut self, predecessor: Option<AccountId>) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetPredecessor(predecessor))
+        }
+
+        /// Brings `account_id`'s records up to `ACCOUNT_VERSION`, one version at a time.
+        ///
+        /// Called whenever an account is written to, so older records are upgraded lazily
+        /// instead of in one unbounded migration after a code upgrade. With only version 1 so
+        /// far, it merely records the version; code that changes the layout of per-account
+        /// records adds its rewrite step here.
+        fn migrate_account(&mut self, account_id: AccountId) {
+            let from_version = self.account_versions.get(account_id).unwrap_or(0);
+            if from_version >= ACCOUNT_VERSION {
+                return;
+            }
+
+            // Version 0 records predate account versioning but already match version 1's
+            // layout, so there is nothing to rewrite yet. Later layouts add one step per
+            // version here, applied in order.
+            self.account_versions.insert(account_id, &ACCOUNT_VERSION);
+
+            // emit event
+            Self::env().emit_event(UpgradedAccount {
+                user: account_id,
+                from_version,
+                to_version: ACCOUNT_VERSION,
+            });
+        }
+
+        /// Upgrades the caller's account records to the current account version.
+        #[ink(message)]
+        pub fn upgrade_account(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            self.migrate_account(caller);
+            Ok(())
+        }
+
+        /// Retrieves the version of a given account ID's records.
+        #[ink(message)]
+        pub fn get_account_version(&self, account_id: AccountId) -> Result<u16> {
+            let _num_entries = self
+                .num_entries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            Ok(self.account_versions.get(account_id).unwrap_or(0))
+        }
+
+        /// Migrates the contract's storage to `STORAGE_VERSION`, one version at a time,
+        /// owner-restricted.
+        ///
+        /// Called once after `set_code_hash` installs code with a newer storage layout version;
+        /// the code upgrade itself already went through the admin timelock.
+        #[ink(message)]
+        pub fn migrate_storage(&mut self) -> Result<()> {
+            self.is_owner()?;
+            let from_version = self.storage_version.get().unwrap_or(0);
+            if from_version >= STORAGE_VERSION {
+                return Err(Error::StorageUpToDate);
+            }
+
+            // Version 0 storage predates storage versioning but already matches version 1's
+            // layout, so there is nothing to rewrite yet. Later layouts add one step per
+            // version here, applied in order.
+            self.storage_version.set(&STORAGE_VERSION);
+
+            // emit event
+            Self::env().emit_event(MigratedStorage {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the storage layout version the storage was migrated to and the version
+        /// this code expects.
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> (u16, u16) {
+            (self.storage_version.get().unwrap_or(0), STORAGE_VERSION)
+        }
+
+        /// Queues replacing the contract's code with the code at `code_hash`, owner-restricted.
+        ///
+        /// ink! decodes the whole root storage on every call, so the new code must keep this
+        /// code's root layout and only add data in new `Mapping`s or `Lazy` fields, running any
+        /// one-off migration of them with `migrate_storage`. Changing the root layout takes a new
+        /// deployment that users move to with `migrate_to_latest`.
+        ///
+        /// Fails once a forwarding proxy is installed; use `set_proxy_target` instead.
+        #[ink(message)]
+        pub fn set_code_hash(&mut self, code_hash: Hash) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetCodeHash(code_hash))
+        }
+
+        /// Retrieves the KeyVault code hash a forwarding proxy delegates calls to, i.e. `Some` only
+        /// when this contract runs behind the proxy.
+        #[ink(message)]
+        pub fn get_proxy_target(&self) -> Option<Hash> {
+            ink::env::get_contract_storage::<u32, Hash>(&PROXY_TARGET_KEY)
+                .ok()
+                .flatten()
+        }
+
+        /// Queues replacing this contract's code with the forwarding proxy at `proxy_code_hash`,
+        /// owner-restricted.
+        ///
+        /// The proxy delegates to the current code, so the contract keeps its address and storage.
+        #[ink(message)]
+        pub fn install_proxy(&mut self, proxy_code_hash: Hash) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::InstallProxy(proxy_code_hash))
+        }
+
+        /// Queues setting the KeyVault code hash a forwarding proxy delegates calls to,
+        /// owner-restricted.
+        ///
+        /// This is how the contract is upgraded once it runs behind the proxy.
+        #[ink(message)]
+        pub fn set_proxy_target(&mut self, code_hash: Hash) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetProxyTarget(code_hash))
+        }
+
+        /// Resets an account, setting its entry count to zero.
+        fn reset(&mut self, account_id: AccountId, encryption_key_hash: &Vec<u8>) {
+            // insert encryption key hash
+            self.encryption_key_hash.insert(account_id, encryption_key_hash);
+            self.num_entries.insert(account_id, &0);
+            self.pending_resets.remove(account_id);
+
+            // emit event
+            Self::env().emit_event(ResetAccount { user: account_id });
+        }
+
+        /// Retrieves the reset delay currently in effect for a given account ID.
+        #[ink(message)]
+        pub fn get_reset_delay(&self, account_id: AccountId) -> Timestamp {
+            let Some(reset_delay) = self.reset_delays.get(account_id) else {
+                return DEFAULT_RESET_DELAY;
+            };
+            let now = self.env().block_timestamp();
+            match reset_delay.pending {
+                Some((delay, effective_at)) if now >= effective_at => delay,
+                _ => reset_delay.delay,
+            }
+        }
+
+        /// Sets the caller's reset delay, with zero opting into instant resets.
+        ///
+        /// Longer delays, up to `MAX_RESET_DELAY`, take effect immediately; shorter ones only after
+        /// the current delay.
+        #[ink(message)]
+        pub fn set_reset_delay(&mut self, delay: Timestamp) -> Result<()> {
+            let caller = self.env().caller();
+            if delay > MAX_RESET_DELAY {
+                return Err(Error::ResetDelayTooLong);
+            }
+
+            // Check if the account exists
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            let current = self.get_reset_delay(caller);
+            let pending = if delay >= current {
+                None
+            } else {
+                let effective_at = self
+                    .env()
+                    .block_timestamp()
+                    .checked_add(current)
+                    .expect("Overflow occurred.");
+                Some((delay, effective_at))
+            };
+            let delay = if pending.is_some() { current } else { delay };
+            self.reset_delays.insert(caller, &ResetDelay { delay, pending });
+            Ok(())
+        }
+
+        /// Requests to reset the caller's account with a new encryption key hash.
+        ///
+        /// The reset can be executed with `execute_reset` once the caller's reset delay has
+        /// elapsed, or happens immediately if the caller opted into instant resets.
+        #[ink(message)]
+        pub fn request_reset(
+            &mut self,
+            encryption_key_hash: Vec<u8>,
+            client_version: SemVer,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_client_version(&client_version)?;
+
+            let _num_entries = self
+                .num_entries
+                .get(caller)
+                .ok_or(Error::AccountNotFound)?;
+            self.check_writable(caller)?;
+
+            let delay = self.get_reset_delay(caller);
+            if delay == 0 {
+                self.reset(caller, &encryption_key_hash);
+                return Ok(());
+            }
+
+            let executable_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(delay)
+                .expect("Overflow occurred.");
+            self.pending_resets.insert(
+                caller,
+                &PendingReset {
+                    encryption_key_hash,
+                    executable_at,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(RequestedReset {
+                user: caller,
+                executable_at,
+            });
+            Ok(())
+        }
+
+        /// Executes the caller's pending reset once its delay has elapsed.
+        #[ink(message)]
+        pub fn execute_reset(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            let pending_reset = self
+                .pending_resets
+                .get(caller)
+                .ok_or(Error::ResetNotFound)?;
+            self.check_writable(caller)?;
+            if self.env().block_timestamp() < pending_reset.executable_at {
+                return Err(Error::ResetPending);
+            }
+
+            self.reset(caller, &pending_reset.encryption_key_hash);
+            Ok(())
+        }
+
+        /// Cancels the caller's pending reset.
+        #[ink(message)]
+        pub fn cancel_reset(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            if self.pending_resets.take(caller).is_none() {
+                return Err(Error::ResetNotFound);
+            }
+
+            // emit event
+            Self::env().emit_event(CancelledReset { user: caller });
+            Ok(())
+        }
+
+        /// Retrieves the pending reset of a given account ID, if any.
+        #[ink(message)]
+        pub fn get_pending_reset(&self, account_id: AccountId) -> Option<PendingReset> {
+            self.pending_resets.get(account_id)
+        }
+
+        /// Retrieves the contract owner's account ID.
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Retrieves the contract owner's account ID.
+        #[ink(message)]
+        pub fn get_owner_result(&self) -> Result<AccountId> {
+            Ok(self.owner)
+        }
+
+        /// Retrieves the version and address of the registry's current release, if a registry is
+        /// set and answers with one.
+        fn get_registry_release(&self) -> Option<(u8, AccountId)> {
+            let registry = self.registry?;
+            let result = build_call::<DefaultEnvironment>()
+                .call(registry)
+                .exec_input(ExecutionInput::new(Selector::new(ink::selector_bytes!(
+                    "get_current_release"
+                ))))
+                .returns::<Option<(u8, AccountId)>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(release)) => release,
+                _ => None,
+            }
+        }
+
+        /// Retrieves the version and address of the latest KeyVault smart contract.
+        ///
+        /// The registry's current release takes precedence when a registry is set and has one,
+        /// falling back to the values stored in this contract otherwise.
+        fn get_latest_release(&self) -> (u8, AccountId) {
+            self.get_registry_release().unwrap_or_else(|| {
+                (
+                    self.latest_smart_contract_version,
+                    self.latest_smart_contract_address
+                        .unwrap_or_else(|| self.env().account_id()),
+                )
+            })
+        }
+
+        /// Retrieves the address of the latest KeyVault smart contract, unless it is this one.
+        fn get_successor(&self) -> Option<AccountId> {
+            let (_, address) = self.get_latest_release();
+            (address != self.env().account_id()).then_some(address)
+        }
+
+        /// Retrieves the version registry's account ID, if any.
+        #[ink(message)]
+        pub fn get_registry(&self) -> Option<AccountId> {
+            self.registry
+        }
+
+        /// Queues setting or clearing the version registry, release manager-restricted.
+        #[ink(message)]
+        pub fn set_registry(&mut self, registry: Option<AccountId>) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetRegistry(registry))
+        }
+
+        /// Retrieves the latest versions for both the KeyVault smart contract and browser extension.
+        ///
+        /// The latest smart contract comes from the version registry when one is set and has a
+        /// current release, falling back to the values stored in this contract otherwise.
+        #[ink(message)]
+        pub fn get_versions(&self) -> Versions {
+            let (latest_smart_contract_version, latest_smart_contract_address) =
+                self.get_latest_release();
+            Versions {
+                current_smart_contract_version: VERSION,
+                latest_smart_contract_version,
+                latest_smart_contract_address,
+                extension_compatibility: self.extension_compatibility.clone(),
+                sunset_deadline: self.sunset_deadline,
+            }
+        }
+
+        /// Publishes a security advisory and returns its advisory ID, owner-restricted.
+        #[ink(message)]
+        pub fn publish_advisory(
+            &mut self,
+            severity: Severity,
+            affected_extension_versions: Vec<VersionRange>,
+            affected_contract_versions: (u8, u8),
+            message: String,
+            url_hash: Hash,
+        ) -> Result<AdvisoryId> {
+            self.is_owner()?;
+            if message.len() > MAX_ADVISORY_MESSAGE_LEN {
+                return Err(Error::AdvisoryMessageTooLong);
+            }
+
+            let advisory_id = self.next_advisory_id;
+            self.next_advisory_id = advisory_id.checked_add(1).expect("Overflow occurred.");
+            self.advisories.insert(
+                advisory_id,
+                &Advisory {
+                    advisory_id,
+                    severity,
+                    affected_extension_versions,
+                    affected_contract_versions,
+                    message,
+                    url_hash,
+                    published_at: self.env().block_timestamp(),
+                    retracted: false,
+                },
+            );
+
+            // emit event
+            Self::env().emit_event(PublishedAdvisory {
+                advisory_id,
+                severity,
+            });
+            Ok(advisory_id)
+        }
+
+        /// Retracts a security advisory, owner-restricted.
+        ///
+        /// The advisory is kept, marked as retracted, so clients can dismiss its warning.
+        #[ink(message)]
+        pub fn retract_advisory(&mut self, advisory_id: AdvisoryId) -> Result<()> {
+            self.is_owner()?;
+            let mut advisory = self
+                .advisories
+                .get(advisory_id)
+                .ok_or(Error::AdvisoryNotFound)?;
+            advisory.retracted = true;
+            self.advisories.insert(advisory_id, &advisory);
+
+            // emit event
+            Self::env().emit_event(RetractedAdvisory { advisory_id });
+            Ok(())
+        }
+
+        /// Retrieves security advisories with IDs from `since_id` on, up to
+        /// `MAX_ADVISORIES_PER_QUERY` at a time, including retracted ones.
+        #[ink(message)]
+        pub fn get_advisories(&self, since_id: AdvisoryId) -> Vec<Advisory> {
+            let last_id = self.min(
+                self.next_advisory_id,
+                since_id.saturating_add(MAX_ADVISORIES_PER_QUERY),
+            );
+            (since_id..last_id)
+                .filter_map(|advisory_id| self.advisories.get(advisory_id))
+                .collect()
+        }
+
+        /// Makes sure the writing client's version is supported and not deprecated.
+        fn check_client_version(&self, client_version: &SemVer) -> Result<()> {
+            let compatibility = &self.extension_compatibility;
+            if *client_version < compatibility.supported.min {
+                return Err(Error::ClientTooOld);
+            }
+            if *client_version > compatibility.supported.max {
+                return Err(Error::ClientTooNew);
+            }
+            if compatibility
+                .deprecated
+                .iter()
+                .any(|range| range.contains(client_version))
+            {
+                return Err(Error::ClientTooOld);
+            }
+            Ok(())
+        }
+
+        /// Queues setting or clearing the sunset deadline, release manager-restricted.
+        #[ink(message)]
+        pub fn set_sunset_deadline(
+            &mut self,
+            deadline: Option<Timestamp>,
+        ) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetSunsetDeadline(deadline))
+        }
+
+        /// Queues updating the latest KeyVault smart contract version, release manager-restricted.
+        #[ink(message)]
+        pub fn set_latest_smart_contract_version(&mut self, latest: u8) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetLatestSmartContractVersion(latest))
+        }
+
+        /// Queues updating the supported and deprecated browser extension versions,
+        /// release manager-restricted.
+        #[ink(message)]
+        pub fn set_extension_compatibility(
+            &mut self,
+            compatibility: ExtensionCompatibility,
+        ) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetExtensionCompatibility(compatibility))
+        }
+
+        /// Queues proposing a new KeyVault smart contract owner, owner-restricted.
+        ///
+        /// Once executed, the proposed owner must call `accept_ownership` for the transfer to
+        /// take effect.
+        #[ink(message)]
+        pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::ProposeOwner(new_owner))
+        }
+
+        /// Accepts ownership of the KeyVault smart contract, restricted to the proposed owner.
+        ///
+        /// The previous owner's roles move to the new owner along with ownership.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::NotPendingOwner);
+            }
+
+            let previous_owner = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            self.hand_over_roles(previous_owner, Some(caller));
+
+            // emit event
+            Self::env().emit_event(TransferredOwnership {
+                previous_owner,
+                new_owner: caller,
+            });
+            Ok(())
+        }
+
+        /// Cancels a proposed ownership transfer, owner-restricted.
+        #[ink(message)]
+        pub fn cancel_ownership_transfer(&mut self) -> Result<()> {
+            self.is_owner()?;
+            let new_owner = self
+                .pending_owner
+                .take()
+                .ok_or(Error::OwnershipTransferNotFound)?;
+
+            // emit event
+            Self::env().emit_event(CancelledOwnershipTransfer {
+                owner: self.owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Queues permanently giving up ownership of the KeyVault smart contract, owner-restricted.
+        ///
+        /// Once executed, the owner is the all-zero account ID, which nobody controls, so
+        /// owner-only messages can never be called again. Owner-only actions still queued, such as
+        /// a proposed owner, are cancelled. The owner's roles are revoked, while roles granted to
+        /// other accounts stay in place for good, since nobody can revoke them.
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::RenounceOwnership)
+        }
+
+        /// Retrieves the proposed next owner, if an ownership transfer is pending.
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Queues updating account creation fee, fee manager-restricted.
+        #[ink(message)]
+        pub fn set_account_creation_fee(&mut self, new_fee: Balance) -> Result<AdminActionId> {
+            self.queue_admin_action(AdminAction::SetAccountCreationFee(new_fee))
+        }
+
+        /// Pays `amount` in fees for `member` from their organization's balance.
+        fn charge_org(&mut self, member: AccountId, amount: Balance) -> Result<()> {
+            let mut membership = self.org_members.get(member).ok_or(Error::NotOrgMember)?;
+            let mut org = self
+                .orgs
+                .get(membership.org_id)
+                .ok_or(Error::OrgNotFound)?;
+
+            let spent = membership.spent.checked_add(amount).expect("Overflow occurred.");
+            if spent > org.member_spending_cap {
+                return Err(Error::OrgSpendingCapExceeded);
+            }
+            org.balance = org
+                .balance
+                .checked_sub(amount)
+                .ok_or(Error::OrgBalanceInsufficient)?;
+            self.total_org_balance = self
+                .total_org_balance
+                .checked_sub(amount)
+                .expect("Underflow occurred.");
+            membership.spent = spent;
+            self.orgs.insert(org.org_id, &org);
+            self.org_members.insert(member, &membership);
+
+            // emit event
+            Self::env().emit_event(ChargedOrg {
+                org_id: org.org_id,
+                member,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Restricts an organization operation to its admin.
+        fn check_org_admin(&self, org_id: OrgId) -> Result<Org> {
+            let org = self.orgs.get(org_id).ok_or(Error::OrgNotFound)?;
+            if self.env().caller() != org.admin {
+                return Err(Error::NotOrgAdmin);
+            }
+            Ok(org)
+        }
+
+        /// Creates an organization with the caller as its admin, prepaid with the attached value.
+        #[ink(message, payable)]
+        pub fn create_org(
+            &mut self,
+            seat_limit: u32,
+            member_spending_cap: Balance,
+        ) -> Result<OrgId> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+            let balance = self.env().transferred_value();
+
+            let org_id = self.next_org_id;
+            self.next_org_id = org_id.checked_add(1).expect("Overflow occurred.");
+            self.orgs.insert(
+                org_id,
+                &Org {
+                    org_id,
+                    admin: caller,
+                    balance,
+                    seat_limit,
+                    seats: 0,
+                    member_spending_cap,
+                },
+            );
+            self.total_org_balance = self
+                .total_org_balance
+                .checked_add(balance)
+                .expect("Overflow occurred.");
+
+            // emit event
+            Self::env().emit_event(CreatedOrg {
+                org_id,
+                admin: caller,
+            });
+            Ok(org_id)
+        }
+
+        /// Tops up an organization's prepaid balance with the attached value.
+        #[ink(message, payable)]
+        pub fn fund_org(&mut self, org_id: OrgId) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let amount = self.env().transferred_value();
+
+            let mut org = self.orgs.get(org_id).ok_or(Error::OrgNotFound)?;
+            org.balance = org.balance.checked_add(amount).expect("Overflow occurred.");
+            self.orgs.insert(org_id, &org);
+            self.total_org_balance = self
+                .total_org_balance
+                .checked_add(amount)
+                .expect("Overflow occurred.");
+
+            // emit event
+            Self::env().emit_event(FundedOrg {
+                org_id,
+                amount,
+                balance: org.balance,
+            });
+            Ok(())
+        }
+
+        /// Withdraws part of an organization's unspent balance to its admin, admin-restricted.
+        ///
+        /// Works while the contract is paused or sunset, so prepaid funds are never locked in.
+        #[ink(message)]
+        pub fn withdraw_org_balance(&mut self, org_id: OrgId, amount: Balance) -> Result<()> {
+            let mut org = self.check_org_admin(org_id)?;
+
+            org.balance = org
+                .balance
+                .checked_sub(amount)
+                .ok_or(Error::OrgBalanceInsufficient)?;
+            self.orgs.insert(org_id, &org);
+            self.total_org_balance = self
+                .total_org_balance
+                .checked_sub(amount)
+                .expect("Underflow occurred.");
+            self.env()
+                .transfer(org.admin, amount)
+                .map_err(|_| Error::TransferFailed)?;
+            Ok(())
+        }
+
+        /// Updates an organization's seat limit and per-member spending cap, admin-restricted.
+        #[ink(message)]
+        pub fn set_org_limits(
+            &mut self,
+            org_id: OrgId,
+            seat_limit: u32,
+            member_spending_cap: Balance,
+        ) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let mut org = self.check_org_admin(org_id)?;
+            org.seat_limit = seat_limit;
+            org.member_spending_cap = member_spending_cap;
+            self.orgs.insert(org_id, &org);
+            Ok(())
+        }
+
+        /// Designates `member` as an organization member whose fees it pays, admin-restricted.
+        #[ink(message)]
+        pub fn add_org_member(&mut self, org_id: OrgId, member: AccountId) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let mut org = self.check_org_admin(org_id)?;
+            if self.org_members.contains(member) {
+                return Err(Error::OrgMemberAlreadyExists);
+            }
+            if org.seats >= org.seat_limit {
+                return Err(Error::OrgSeatLimitReached);
+            }
+
+            org.seats = org.seats.checked_add(1).expect("Overflow occurred.");
+            self.orgs.insert(org_id, &org);
+            self.org_members
+                .insert(member, &OrgMembership { org_id, spent: 0 });
+
+            // emit event
+            Self::env().emit_event(AddedOrgMember { org_id, member });
+            Ok(())
+        }
+
+        /// Stops paying fees for `member`, freeing their seat, admin-restricted.
+        #[ink(message)]
+        pub fn remove_org_member(&mut self, org_id: OrgId, member: AccountId) -> Result<()> {
+            self.check_not_read_only()?;
+
+            let mut org = self.check_org_admin(org_id)?;
+            match self.org_members.get(member) {
+                Some(membership) if membership.org_id == org_id => {
+                    self.org_members.remove(member);
+                }
+                _ => return Err(Error::NotOrgMember),
+            }
+
+            org.seats = org.seats.saturating_sub(1);
+            self.orgs.insert(org_id, &org);
+
+            // emit event
+            Self::env().emit_event(RemovedOrgMember { org_id, member });
+            Ok(())
+        }
+
+        /// Leaves the caller's organization, freeing their seat.
+        ///
+        /// Admins add members without their consent, so members can always leave, e.g. to stop
+        /// an organization that can't pay from standing between them and their own payment.
+        #[ink(message)]
+        pub fn leave_org(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            self.check_not_read_only()?;
+
+            let membership = self.org_members.take(caller).ok_or(Error::NotOrgMember)?;
+            if let Some(mut org) = self.orgs.get(membership.org_id) {
+                org.seats = org.seats.saturating_sub(1);
+                self.orgs.insert(membership.org_id, &org);
+            }
+
+            // emit event
+            Self::env().emit_event(RemovedOrgMember {
+                org_id: membership.org_id,
+                member: caller,
+            });
+            Ok(())
+        }
+
+        /// Retrieves an organization.
+        #[ink(message)]
+        pub fn get_org(&self, org_id: OrgId) -> Result<Org> {
+            self.orgs.get(org_id).ok_or(Error::OrgNotFound)
+        }
+
+        /// Retrieves an organization's prepaid balance.
+        #[ink(message)]
+        pub fn org_balance(&self, org_id: OrgId) -> Result<Balance> {
+            self.orgs
+                .get(org_id)
+                .map(|org| org.balance)
+                .ok_or(Error::OrgNotFound)
+        }
+
+        /// Retrieves a given account ID's organization membership, if any.
+        #[ink(message)]
+        pub fn get_org_membership(&self, account_id: AccountId) -> Option<OrgMembership> {
+            self.org_members.get(account_id)
+        }
+
+        /// Gets the smart contract's balance.
+        #[ink(message)]
+        pub fn get_balance(&self) -> Balance {
+            self.env().balance()
+        }
+
+        /// Withdraws the smart contract's balance, excluding organizations' prepaid balances,
+        /// to the caller, treasurer-restricted.
+        #[ink(message)]
+        pub fn withdraw(&mut self) -> Result<()> {
+            self.check_role(Role::Treasurer)?;
+
+            let balance = self
+                .env()
+                .balance()
+                .saturating_sub(self.total_org_balance);
+            if balance > 0 {
+                self.env()
+                    .transfer(self.env().caller(), balance)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        type Env = ink::env::DefaultEnvironment;
+
+        const CLIENT_VERSION: SemVer = SemVer {
+            major: 0,
+            minor: 1,
+            patch: 0,
+        };
+
+        fn accounts() -> ink::env::test::DefaultAccounts<Env> {
+            ink::env::test::default_accounts::<Env>()
+        }
+
+        fn set_caller(account: AccountId) {
+            ink::env::test::set_caller::<Env>(account);
+        }
+
+        fn set_time(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<Env>(timestamp);
+        }
+
+        /// Deploys a KeyVault owned by Alice, with Bob holding an account.
+        fn setup() -> KeyVault {
+            let accounts = accounts();
+            set_time(1);
+            set_caller(accounts.alice);
+            let mut keyvault = KeyVault::new(accounts.alice, CLIENT_VERSION);
+
+            set_caller(accounts.bob);
+            keyvault
+                .create_account(Vec::from([1]), None, CLIENT_VERSION)
+                .unwrap();
+            keyvault
+        }
+
+        fn add_entry(keyvault: &mut KeyVault, expected_index: u32) -> Result<()> {
+            keyvault.add_entry(
+                expected_index,
+                Vec::from([0; 12]),
+                Vec::from([1, 2, 3]),
+                None,
+                CLIENT_VERSION,
+            )
+        }
+
+        #[ink::test]
+        fn frozen_account_rejects_writes_until_unfrozen_after_delay() {
+            let mut keyvault = setup();
+
+            keyvault.freeze_account().unwrap();
+            assert_eq!(add_entry(&mut keyvault, 0), Err(Error::AccountFrozen));
+            assert_eq!(keyvault.unfreeze_account(), Err(Error::UnfreezePending));
+
+            keyvault.request_unfreeze().unwrap();
+            set_time(1 + UNFREEZE_DELAY - 1);
+            assert_eq!(keyvault.unfreeze_account(), Err(Error::UnfreezePending));
+
+            set_time(1 + UNFREEZE_DELAY);
+            keyvault.unfreeze_account().unwrap();
+            assert_eq!(keyvault.get_freeze(accounts().bob), None);
+            assert_eq!(add_entry(&mut keyvault, 0), Ok(()));
+        }
+
+        #[ink::test]
+        fn frozen_account_rejects_safeguard_changes() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            keyvault
+                .set_emergency_contact(accounts.charlie, 0, Vec::from([1]))
+                .unwrap();
+            keyvault
+                .register_device(1, Vec::new(), Vec::new())
+                .unwrap();
+            keyvault.freeze_account().unwrap();
+
+            assert_eq!(
+                keyvault.set_emergency_contact(accounts.charlie, 0, Vec::new()),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(
+                keyvault.remove_emergency_contact(accounts.charlie),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(
+                keyvault.register_device(2, Vec::new(), Vec::new()),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(
+                keyvault.grant_session(
+                    accounts.django,
+                    SessionPermissions {
+                        read: true,
+                        append: true,
+                        update: false,
+                        delete: false,
+                    },
+                    Timestamp::MAX,
+                    None,
+                ),
+                Err(Error::AccountFrozen)
+            );
+
+            // cutting off a stolen device still works
+            keyvault.revoke_device(1).unwrap();
+        }
+
+        #[ink::test]
+        fn freezing_again_cancels_unfreeze_request() {
+            let mut keyvault = setup();
+
+            keyvault.freeze_account().unwrap();
+            keyvault.request_unfreeze().unwrap();
+            keyvault.freeze_account().unwrap();
+
+            set_time(1 + UNFREEZE_DELAY);
+            assert_eq!(keyvault.unfreeze_account(), Err(Error::UnfreezePending));
+        }
+
+        #[ink::test]
+        fn reset_waits_for_reset_delay() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+            add_entry(&mut keyvault, 0).unwrap();
+
+            keyvault
+                .request_reset(Vec::from([2]), CLIENT_VERSION)
+                .unwrap();
+            assert_eq!(keyvault.execute_reset(), Err(Error::ResetPending));
+            assert_eq!(keyvault.get_encryption_key_hash(bob), Ok(Vec::from([1])));
+
+            set_time(1 + DEFAULT_RESET_DELAY);
+            keyvault.execute_reset().unwrap();
+            assert_eq!(keyvault.get_encryption_key_hash(bob), Ok(Vec::from([2])));
+            assert_eq!(keyvault.get_pending_reset(bob), None);
+            assert_eq!(add_entry(&mut keyvault, 0), Ok(()));
+        }
+
+        #[ink::test]
+        fn cancelled_reset_cannot_be_executed() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+
+            keyvault
+                .request_reset(Vec::from([2]), CLIENT_VERSION)
+                .unwrap();
+            keyvault.cancel_reset().unwrap();
+
+            set_time(1 + DEFAULT_RESET_DELAY);
+            assert_eq!(keyvault.execute_reset(), Err(Error::ResetNotFound));
+            assert_eq!(keyvault.get_encryption_key_hash(bob), Ok(Vec::from([1])));
+        }
+
+        #[ink::test]
+        fn shorter_reset_delay_only_applies_after_current_delay() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+
+            keyvault.set_reset_delay(0).unwrap();
+            assert_eq!(keyvault.get_reset_delay(bob), DEFAULT_RESET_DELAY);
+
+            set_time(1 + DEFAULT_RESET_DELAY);
+            assert_eq!(keyvault.get_reset_delay(bob), 0);
+            keyvault
+                .request_reset(Vec::from([2]), CLIENT_VERSION)
+                .unwrap();
+            assert_eq!(keyvault.get_encryption_key_hash(bob), Ok(Vec::from([2])));
+        }
+
+        #[ink::test]
+        fn reset_delay_is_capped() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+
+            assert_eq!(
+                keyvault.set_reset_delay(Timestamp::MAX),
+                Err(Error::ResetDelayTooLong)
+            );
+            keyvault.set_reset_delay(MAX_RESET_DELAY).unwrap();
+            assert_eq!(keyvault.get_reset_delay(bob), MAX_RESET_DELAY);
+
+            // the account can still be reset, just after the longest delay
+            keyvault
+                .request_reset(Vec::from([2]), CLIENT_VERSION)
+                .unwrap();
+            set_time(1 + MAX_RESET_DELAY);
+            keyvault.execute_reset().unwrap();
+            assert_eq!(keyvault.get_encryption_key_hash(bob), Ok(Vec::from([2])));
+        }
+
+        /// Sets the account creation fee through the admin timelock, leaving the clock at `at`.
+        fn set_fee(keyvault: &mut KeyVault, fee: Balance, at: Timestamp) {
+            set_caller(accounts().alice);
+            let action_id = keyvault.set_account_creation_fee(fee).unwrap();
+            set_time(at);
+            keyvault.execute_admin_action(action_id).unwrap();
+        }
+
+        #[ink::test]
+        fn org_pays_member_fees_up_to_its_balance_and_cap() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            set_fee(&mut keyvault, 10, 1 + DEFAULT_ADMIN_DELAY);
+
+            set_caller(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(15);
+            let org_id = keyvault.create_org(2, 10).unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+            keyvault.add_org_member(org_id, accounts.django).unwrap();
+            keyvault.add_org_member(org_id, accounts.eve).unwrap();
+
+            set_caller(accounts.django);
+            keyvault
+                .create_account(Vec::from([1]), None, CLIENT_VERSION)
+                .unwrap();
+            assert_eq!(keyvault.get_org(org_id).map(|org| org.balance), Ok(5));
+
+            // once the organization can't pay, members pay the fee themselves
+            set_caller(accounts.eve);
+            assert_eq!(
+                keyvault.create_account(Vec::from([1]), None, CLIENT_VERSION),
+                Err(Error::InsufficientPayment)
+            );
+            ink::env::test::set_value_transferred::<Env>(10);
+            keyvault
+                .create_account(Vec::from([1]), None, CLIENT_VERSION)
+                .unwrap();
+            assert_eq!(keyvault.get_org(org_id).map(|org| org.balance), Ok(5));
+        }
+
+        #[ink::test]
+        fn members_can_leave_org() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            set_caller(accounts.charlie);
+            let org_id = keyvault.create_org(1, 0).unwrap();
+            keyvault.add_org_member(org_id, accounts.django).unwrap();
+            assert_eq!(
+                keyvault.add_org_member(org_id, accounts.eve),
+                Err(Error::OrgSeatLimitReached)
+            );
 
-        /// Version number of the latest browser extension that is compatible with this smart contract.
-        ///
-        /// It ensures users are aware of the necessary software version for optimal interaction.
-        latest_compatible_browser_extension_version: u32,
-    }
+            set_caller(accounts.django);
+            keyvault.leave_org().unwrap();
+            assert_eq!(keyvault.get_org_membership(accounts.django), None);
+            assert_eq!(keyvault.leave_org(), Err(Error::NotOrgMember));
 
-    impl KeyVault {
-        #[ink(constructor)]
-        pub fn new(owner: AccountId, latest_compatible_browser_extension_version: u32) -> Self {
-            Self {
-                owner,
-                encryption_key_hash: Mapping::new(),
-                entries: Mapping::new(),
-                num_entries: Mapping::new(),
-                latest_smart_contract_version: VERSION,
-                latest_smart_contract_address: None,
-                latest_compatible_browser_extension_version,
-                fee: 0,
-            }
+            set_caller(accounts.charlie);
+            keyvault.add_org_member(org_id, accounts.eve).unwrap();
         }
 
-        /// Restricts a transaction to the contract owner.
-        fn is_owner(&self) -> Result<()> {
-            if self.env().caller() != self.owner {
-                return Err(Error::NotOwner);
-            }
-            Ok(())
+        #[ink::test]
+        fn storage_migrates_once_to_current_version() {
+            let mut keyvault = setup();
+            assert_eq!(keyvault.get_storage_version(), (STORAGE_VERSION, STORAGE_VERSION));
+
+            // storage written before storage versioning carries no version
+            ink::env::clear_contract_storage(&STORAGE_VERSION_KEY);
+            assert_eq!(keyvault.get_storage_version(), (0, STORAGE_VERSION));
+
+            set_caller(accounts().bob);
+            assert_eq!(keyvault.migrate_storage(), Err(Error::NotOwner));
+
+            set_caller(accounts().alice);
+            keyvault.migrate_storage().unwrap();
+            assert_eq!(keyvault.get_storage_version(), (STORAGE_VERSION, STORAGE_VERSION));
+            assert_eq!(keyvault.migrate_storage(), Err(Error::StorageUpToDate));
         }
 
-        /// Sets the address of the latest KeyVault smart contract, owner-restricted.
-        #[ink(message)]
-        pub fn set_latest_smart_contract_address(&mut self, latest: AccountId) -> Result<()> {
-            self.is_owner()?;
-            self.latest_smart_contract_address = Some(latest);
-            Ok(())
+        #[ink::test]
+        fn account_records_upgrade_lazily_on_write() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+            assert_eq!(keyvault.get_account_version(bob), Ok(ACCOUNT_VERSION));
+
+            // records written before account versioning carry no version
+            keyvault.account_versions.remove(bob);
+            assert_eq!(keyvault.get_account_version(bob), Ok(0));
+
+            add_entry(&mut keyvault, 0).unwrap();
+            assert_eq!(keyvault.get_account_version(bob), Ok(ACCOUNT_VERSION));
         }
 
-        /// Constructs a unique key for entry mapping from an account ID and index.
-        fn construct_key(account_id: AccountId, index: u32) -> Vec<u8> {
-            let mut key = Vec::from(account_id.as_ref() as &[u8]);
-            key.extend_from_slice(&index.to_le_bytes());
-            key
+        #[ink::test]
+        fn encryption_pubkeys_keep_their_proofs_and_history() {
+            let mut keyvault = setup();
+            let bob = accounts().bob;
+            assert_eq!(
+                keyvault.get_encryption_pubkey(bob),
+                Err(Error::EncryptionPubkeyNotFound)
+            );
+
+            keyvault.set_encryption_pubkey([1; 32], [2; 64]).unwrap();
+            set_time(2);
+            keyvault.set_encryption_pubkey([3; 32], [4; 64]).unwrap();
+
+            assert_eq!(keyvault.get_encryption_pubkey(bob), Ok([3; 32]));
+            let history = keyvault.get_encryption_pubkey_history(bob);
+            assert_eq!(history.len(), 2);
+            assert_eq!((history[0].pubkey, history[0].proof), ([1; 32], [2; 64]));
+            assert_eq!((history[1].pubkey, history[1].proof), ([3; 32], [4; 64]));
+
+            set_caller(accounts().charlie);
+            assert_eq!(
+                keyvault.set_encryption_pubkey([5; 32], [6; 64]),
+                Err(Error::AccountNotFound)
+            );
         }
 
-        /// Creates account
-        #[ink(message, payable)]
-        pub fn create_account(&mut self, encryption_key_hash: Vec<u8>) -> Result<()> {
-            let caller = self.env().caller();
-            let attached_deposit = self.env().transferred_value();
+        #[ink::test]
+        fn guardians_approve_recovery_per_new_account() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let guardians = Vec::from([accounts.charlie, accounts.django, accounts.eve]);
+            keyvault.set_guardians(guardians, 2, 0).unwrap();
+            set_time(1 + DEFAULT_RESET_DELAY);
 
-            // make sure account does not already exist
-            if self.num_entries.get(caller).is_some() {
-                return Err(Error::AccountAlreadyExists);
-            // make sure sufficient payment is sent
-            } else if attached_deposit < self.fee {
-                return Err(Error::InsufficientPayment);
-            // "create" account
-            } else {
-                // set hash of encryption key
-                self.encryption_key_hash
-                    .insert(caller, &encryption_key_hash);
-                self.num_entries.insert(caller, &0);
-            }
+            set_caller(accounts.charlie);
+            keyvault.recover_to(accounts.bob, accounts.frank).unwrap();
+            // a guardian backing another account leaves the approvals for frank alone
+            set_caller(accounts.eve);
+            keyvault.recover_to(accounts.bob, accounts.alice).unwrap();
+            assert_eq!(
+                keyvault.execute_recovery(accounts.bob, accounts.frank),
+                Err(Error::RecoveryPending)
+            );
 
-            // emit event
-            Self::env().emit_event(Registered { user: caller });
-            Ok(())
+            set_caller(accounts.django);
+            keyvault.recover_to(accounts.bob, accounts.frank).unwrap();
+            assert_eq!(keyvault.get_recoveries(accounts.bob).len(), 2);
+            keyvault
+                .execute_recovery(accounts.bob, accounts.frank)
+                .unwrap();
+            assert_eq!(
+                keyvault.get_account_move(accounts.bob).map(|account_move| account_move.to),
+                Some(accounts.frank)
+            );
         }
 
-        /// Retrieves the hash of the encryption key of an AccountId
-        #[ink(message)]
-        pub fn get_encryption_key_hash(&self, account_id: AccountId) -> Result<Vec<u8>> {
-            let result = self
-                .encryption_key_hash
-                .get(account_id)
-                .ok_or(Error::AccountNotFound)?;
-            Ok(result)
+        #[ink::test]
+        fn guardians_switching_candidates_withdraw_their_approval() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let guardians = Vec::from([accounts.charlie, accounts.django]);
+            keyvault.set_guardians(guardians, 2, 0).unwrap();
+            set_time(1 + DEFAULT_RESET_DELAY);
+
+            set_caller(accounts.charlie);
+            keyvault.recover_to(accounts.bob, accounts.frank).unwrap();
+            set_caller(accounts.django);
+            keyvault.recover_to(accounts.bob, accounts.frank).unwrap();
+            set_caller(accounts.charlie);
+            keyvault.recover_to(accounts.bob, accounts.eve).unwrap();
+
+            assert_eq!(
+                keyvault.execute_recovery(accounts.bob, accounts.frank),
+                Err(Error::RecoveryPending)
+            );
+            assert_eq!(
+                keyvault.execute_recovery(accounts.bob, accounts.eve),
+                Err(Error::RecoveryPending)
+            );
         }
 
-        /// Adds a new encrypted entry for the caller, ensuring sequential order.
-        #[ink(message)]
-        pub fn add_entry(
-            &mut self,
-            expected_index: u32,
-            iv: Vec<u8>,
-            ciphertext: Vec<u8>,
-        ) -> Result<()> {
-            let caller = self.env().caller();
+        #[ink::test]
+        fn guardians_must_be_distinct() {
+            let mut keyvault = setup();
+            let charlie = accounts().charlie;
 
-            // Check if the account exists
-            let current_index = self
-                .num_entries
-                .get(caller)
-                .ok_or(Error::AccountNotFound)?;
+            assert_eq!(
+                keyvault.set_guardians(Vec::from([charlie, charlie]), 2, 0),
+                Err(Error::DuplicateGuardian)
+            );
+            assert_eq!(
+                keyvault.set_guardians(Vec::from([charlie]), 2, 0),
+                Err(Error::InvalidThreshold)
+            );
+        }
 
-            if expected_index != current_index {
-                return Err(Error::IndexMismatch);
-            }
+        #[ink::test]
+        fn registered_devices_must_tag_writes_until_revoked() {
+            let mut keyvault = setup();
+            add_entry(&mut keyvault, 0).unwrap();
 
-            let key = Self::construct_key(caller, expected_index);
-            self.entries
-                .insert(&key, &EncryptedEntry { iv, ciphertext });
-            let num_entries = current_index.checked_add(1).expect("Overflow occurred.");
-            self.num_entries.insert(caller, &num_entries);
+            keyvault.register_device(1, Vec::new(), Vec::new()).unwrap();
+            assert_eq!(add_entry(&mut keyvault, 1), Err(Error::DeviceRequired));
+            let tagged = |keyvault: &mut KeyVault, device_id| {
+                keyvault.add_entry(1, Vec::new(), Vec::new(), Some(device_id), CLIENT_VERSION)
+            };
+            assert_eq!(tagged(&mut keyvault, 2), Err(Error::DeviceNotFound));
+            assert_eq!(tagged(&mut keyvault, 1), Ok(()));
 
-            // emit event
-            Self::env().emit_event(AddedEntry {
-                user: caller,
-                num_entries,
-            });
-            Ok(())
+            keyvault.revoke_device(1).unwrap();
+            assert_eq!(
+                keyvault.add_entry(2, Vec::new(), Vec::new(), Some(1), CLIENT_VERSION),
+                Err(Error::DeviceRevoked)
+            );
+            assert_eq!(add_entry(&mut keyvault, 2), Err(Error::DeviceRequired));
         }
 
-        /// Adds new encrypted entries for the caller, ensuring sequential order.
-        #[ink(message)]
-        pub fn add_entries(
-            &mut self,
-            expected_index: u32,
-            entries: Vec<(Vec<u8>, Vec<u8>)>,
-        ) -> Result<()> {
-            let caller = self.env().caller();
+        /// Signs a relayed write as `owner` would for `relay_add_entries`.
+        fn sign_relay(
+            owner: &ink_e2e::Keypair,
+            nonce: u64,
+            deadline: Timestamp,
+            payload: &RelayPayload,
+        ) -> AccountSignature {
+            let contract = ink::env::account_id::<Env>();
+            let owner_id = AccountId::from(owner.public_key().0);
+            let message = (contract, owner_id, nonce, deadline, payload).encode();
+            AccountSignature::Sr25519(owner.sign(&message).0)
+        }
 
-            // Check if the account exists
-            let current_index = self
-                .num_entries
-                .get(caller)
-                .ok_or(Error::AccountNotFound)?;
+        #[ink::test]
+        fn relayed_writes_need_owner_signature_and_fresh_nonce() {
+            let mut keyvault = setup();
+            let owner = ink_e2e::alice();
+            let owner_id = AccountId::from(owner.public_key().0);
+            set_caller(owner_id);
+            keyvault
+                .create_account(Vec::from([1]), None, CLIENT_VERSION)
+                .unwrap();
 
-            if expected_index != current_index {
-                return Err(Error::IndexMismatch);
-            }
+            set_caller(accounts().charlie);
+            let payload = RelayPayload {
+                expected_index: 0,
+                entries: Vec::from([(Vec::from([0; 12]), Vec::from([1, 2, 3]))]),
+                device_id: None,
+                client_version: CLIENT_VERSION,
+            };
+            let signature = sign_relay(&owner, 0, 10, &payload);
 
-            let entries_len = entries.len() as u32;
+            // someone else's signature, or the owner's over other terms, is rejected
+            assert_eq!(
+                keyvault.relay_add_entries(
+                    owner_id,
+                    0,
+                    10,
+                    payload.clone(),
+                    sign_relay(&ink_e2e::bob(), 0, 10, &payload),
+                ),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                keyvault.relay_add_entries(owner_id, 0, 11, payload.clone(), signature.clone()),
+                Err(Error::InvalidSignature)
+            );
 
-            // `entries` is assumed to be a vector of (iv, ciphertext)
-            for (i, (iv, ciphertext)) in entries.into_iter().enumerate() {
-                let idx = current_index.checked_add(i as u32).expect("Overflow occurred.");
-                let key = Self::construct_key(caller, idx);
-                self.entries
-                    .insert(&key, &EncryptedEntry { iv, ciphertext });
+            keyvault
+                .relay_add_entries(owner_id, 0, 10, payload.clone(), signature.clone())
+                .unwrap();
+            assert_eq!(keyvault.get_entry_count(owner_id), Ok(1));
+            assert_eq!(keyvault.get_relay_nonce(owner_id), 1);
+
+            // the same signed write can't be replayed
+            assert_eq!(
+                keyvault.relay_add_entries(owner_id, 0, 10, payload.clone(), signature),
+                Err(Error::InvalidNonce)
+            );
+
+            // nor submitted after its deadline
+            let payload = RelayPayload {
+                expected_index: 1,
+                ..payload
+            };
+            let signature = sign_relay(&owner, 1, 10, &payload);
+            set_time(11);
+            assert_eq!(
+                keyvault.relay_add_entries(owner_id, 1, 10, payload, signature),
+                Err(Error::RelayExpired)
+            );
+        }
+
+        #[ink::test]
+        fn transfers_wait_for_reset_delay_and_move_entries_in_chunks() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            for index in 0..3 {
+                add_entry(&mut keyvault, index).unwrap();
             }
+            keyvault.propose_account_transfer(accounts.frank).unwrap();
 
-            // Update `num_entries` for the caller after all entries have been added
-            let num_entries = current_index.checked_add(entries_len).expect("Overflow occurred.");
-            self.num_entries
-                .insert(caller, &num_entries);
+            set_caller(accounts.eve);
+            assert_eq!(
+                keyvault.accept_account_transfer(accounts.bob),
+                Err(Error::AccountTransferNotFound)
+            );
+            set_caller(accounts.frank);
+            assert_eq!(
+                keyvault.accept_account_transfer(accounts.bob),
+                Err(Error::AccountTransferPending)
+            );
+            set_time(1 + DEFAULT_RESET_DELAY);
+            keyvault.accept_account_transfer(accounts.bob).unwrap();
 
-            // emit event
-            Self::env().emit_event(AddedEntry {
-                user: caller,
-                num_entries,
-            });
+            // the vault is read-only while its entries move
+            set_caller(accounts.bob);
+            assert_eq!(add_entry(&mut keyvault, 3), Err(Error::AccountMoving));
 
-            Ok(())
+            assert_eq!(keyvault.continue_account_move(accounts.bob, 2), Ok(false));
+            assert_eq!(keyvault.continue_account_move(accounts.bob, 2), Ok(true));
+            assert_eq!(
+                keyvault.continue_account_move(accounts.bob, 2),
+                Err(Error::AccountMoveNotFound)
+            );
+
+            assert_eq!(keyvault.get_entry_count(accounts.bob), Err(Error::AccountNotFound));
+            assert_eq!(keyvault.get_entry_count(accounts.frank), Ok(3));
+            assert_eq!(
+                keyvault.get_encryption_key_hash(accounts.frank),
+                Ok(Vec::from([1]))
+            );
+            assert_eq!(
+                keyvault.get_entry(accounts.frank, 2).map(|entry| entry.ciphertext),
+                Ok(Vec::from([1, 2, 3]))
+            );
+
+            set_caller(accounts.frank);
+            assert_eq!(add_entry(&mut keyvault, 3), Ok(()));
         }
 
-        /// Retrieves the number of entries for a given account ID.
-        #[ink(message)]
-        pub fn get_entry_count(&self, account_id: AccountId) -> Result<u32> {
-            self.num_entries
-                .get(account_id)
-                .ok_or(Error::AccountNotFound)
+        fn recovery_commitment(
+            code: &Vec<u8>,
+            action: &RecoveryCodeAction,
+            account_id: AccountId,
+        ) -> Hash {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(code, action, account_id), &mut output);
+            Hash::from(output)
         }
 
-        /// Retrieves an encrypted entry by index for a given account ID.
-        #[ink(message)]
-        pub fn get_entry(&self, account_id: AccountId, index: u32) -> Result<EncryptedEntry> {
-            let num = self
-                .num_entries
-                .get(account_id)
-                .ok_or(Error::AccountNotFound)?;
-            if index >= num {
-                return Err(Error::IndexMismatch);
-            }
+        #[ink::test]
+        fn recovery_code_must_be_committed_in_an_earlier_block() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let code = Vec::from([7; 16]);
+            let mut code_hash = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(&code, &mut code_hash);
 
-            let key = Self::construct_key(account_id, index);
-            self.entries.get(key).ok_or(Error::AccountNotFound)
+            keyvault.set_recovery_code_hash(Vec::from(code_hash)).unwrap();
+            assert!(!keyvault.has_recovery_code(accounts.bob));
+            set_time(1 + DEFAULT_RESET_DELAY);
+            assert!(keyvault.has_recovery_code(accounts.bob));
+
+            let action = RecoveryCodeAction::ResetKey(Vec::from([2]));
+            set_caller(accounts.eve);
+            keyvault
+                .commit_recovery_code(
+                    accounts.bob,
+                    recovery_commitment(&code, &action, accounts.bob),
+                )
+                .unwrap();
+            assert_eq!(
+                keyvault.use_recovery_code(accounts.bob, code.clone(), action.clone()),
+                Err(Error::RecoveryCodeNotCommitted)
+            );
+
+            // revealing too early uses up the commitment
+            ink::env::test::advance_block::<Env>();
+            assert_eq!(
+                keyvault.use_recovery_code(accounts.bob, code.clone(), action.clone()),
+                Err(Error::RecoveryCodeNotCommitted)
+            );
+            keyvault
+                .commit_recovery_code(
+                    accounts.bob,
+                    recovery_commitment(&code, &action, accounts.bob),
+                )
+                .unwrap();
+
+            ink::env::test::advance_block::<Env>();
+            assert_eq!(
+                keyvault.use_recovery_code(accounts.bob, Vec::from([8; 16]), action.clone()),
+                Err(Error::InvalidRecoveryCode)
+            );
+            // the revealed code can't be redirected to an action nobody committed to
+            assert_eq!(
+                keyvault.use_recovery_code(
+                    accounts.bob,
+                    code.clone(),
+                    RecoveryCodeAction::MoveTo(accounts.eve)
+                ),
+                Err(Error::RecoveryCodeNotCommitted)
+            );
+            keyvault
+                .use_recovery_code(accounts.bob, code.clone(), action.clone())
+                .unwrap();
+            assert_eq!(
+                keyvault.get_encryption_key_hash(accounts.bob),
+                Ok(Vec::from([2]))
+            );
+
+            // the code is single-use
+            assert!(!keyvault.has_recovery_code(accounts.bob));
+            assert_eq!(
+                keyvault.use_recovery_code(accounts.bob, code, action),
+                Err(Error::RecoveryCodeNotSet)
+            );
         }
 
-        fn min(&self, a: u32, b: u32) -> u32 {
-            if a <= b {
-                a
-            } else {
-                b
-            }
+        #[ink::test]
+        fn inbox_caps_shares_per_sender_and_marks_accepted_entries() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            set_caller(accounts.charlie);
+            keyvault
+                .create_account(Vec::from([2]), None, CLIENT_VERSION)
+                .unwrap();
+
+            set_caller(accounts.eve);
+            assert_eq!(
+                keyvault.send_share(accounts.charlie, Vec::from([1]), [1; 32]),
+                Err(Error::AccountNotFound)
+            );
+
+            set_caller(accounts.bob);
+            let share_ids: Vec<ShareId> = (0..MAX_INBOX_SHARES_PER_SENDER)
+                .map(|_| {
+                    keyvault
+                        .send_share(accounts.charlie, Vec::from([1]), [1; 32])
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(
+                keyvault.send_share(accounts.charlie, Vec::from([1]), [1; 32]),
+                Err(Error::InboxFull)
+            );
+
+            // declining makes room for the sender again
+            set_caller(accounts.charlie);
+            keyvault.decline_share(share_ids[0]).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(keyvault.decline_share(share_ids[1]), Err(Error::ShareNotFound));
+            keyvault
+                .send_share(accounts.charlie, Vec::from([4, 5, 6]), [9; 32])
+                .unwrap();
+            assert_eq!(
+                keyvault.list_inbox(accounts.charlie).len(),
+                MAX_INBOX_SHARES_PER_SENDER
+            );
+
+            set_caller(accounts.charlie);
+            let share_id = keyvault.list_inbox(accounts.charlie).last().unwrap().share_id;
+            keyvault.accept_share(share_id).unwrap();
+            let entry = keyvault.get_entry(accounts.charlie, 0).unwrap();
+            assert_eq!(entry.shared_by, Some(accounts.bob));
+            assert_eq!(entry.iv, Vec::from([9; 32]));
+            assert_eq!(entry.ciphertext, Vec::from([4, 5, 6]));
+            assert_eq!(keyvault.accept_share(share_id), Err(Error::ShareNotFound));
         }
 
-        /// Retrieves the encrypted entries requested for a given account ID.
-        #[ink(message)]
-        pub fn get_entries(
-            &self,
-            account_id: AccountId,
-            start_index: u32,
-            max_num: u32,
-        ) -> Result<Vec<EncryptedEntry>> {
-            let num = self
-                .num_entries
-                .get(account_id)
-                .ok_or(Error::AccountNotFound)?;
-            if start_index >= num {
-                return Err(Error::IndexMismatch);
+        fn team_metadata(scheme_id: u8, kdf_iterations: u32) -> EntryMetadata {
+            EntryMetadata {
+                scheme_id,
+                kdf_iterations,
+                extension_version: CLIENT_VERSION,
             }
+        }
 
-            let mut results = Vec::new();
-            let last_index = start_index.checked_add(max_num).expect("Overflow occurred.");
-            for index in start_index..self.min(num, last_index) {
-                let key = Self::construct_key(account_id, index);
-                let entry = self.entries.get(&key).ok_or(Error::AccountNotFound)?;
-                results.push(entry);
-            }
+        #[ink::test]
+        fn team_roles_gate_writes_and_membership() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let team_id = keyvault.create_team(Vec::from([1])).unwrap();
+            keyvault
+                .add_member(team_id, accounts.charlie, TeamRole::Reader, Vec::from([2]))
+                .unwrap();
+            assert_eq!(
+                keyvault.add_member(team_id, accounts.charlie, TeamRole::Writer, Vec::new()),
+                Err(Error::TeamMemberAlreadyExists)
+            );
 
-            Ok(results)
-        }
+            set_caller(accounts.charlie);
+            assert_eq!(
+                keyvault.add_team_entry(team_id, 0, Vec::new(), Vec::new(), team_metadata(1, 0)),
+                Err(Error::InsufficientTeamRole)
+            );
+            assert_eq!(
+                keyvault.add_member(team_id, accounts.django, TeamRole::Admin, Vec::new()),
+                Err(Error::InsufficientTeamRole)
+            );
+            set_caller(accounts.eve);
+            assert_eq!(
+                keyvault.add_team_entry(team_id, 0, Vec::new(), Vec::new(), team_metadata(1, 0)),
+                Err(Error::NotTeamMember)
+            );
 
-        /// Resets the caller's account, setting their entry count to zero.
-        #[ink(message)]
-        pub fn reset_account(&mut self, encryption_key_hash: Vec<u8>) -> Result<()> {
-            let caller = self.env().caller();
+            set_caller(accounts.bob);
+            keyvault
+                .set_role(team_id, accounts.charlie, TeamRole::Writer)
+                .unwrap();
+            set_caller(accounts.charlie);
+            keyvault
+                .add_team_entry(team_id, 0, Vec::new(), Vec::from([1]), team_metadata(1, 0))
+                .unwrap();
 
-            let _num_entries = self
-                .num_entries
-                .get(caller)
-                .ok_or(Error::AccountNotFound)?;
+            // the only admin can neither step down nor be removed
+            set_caller(accounts.bob);
+            assert_eq!(
+                keyvault.set_role(team_id, accounts.bob, TeamRole::Writer),
+                Err(Error::LastTeamAdmin)
+            );
+            assert_eq!(
+                keyvault.remove_member(team_id, accounts.bob),
+                Err(Error::LastTeamAdmin)
+            );
 
-            // insert encryption key hash
-            self.encryption_key_hash.insert(caller, &encryption_key_hash);
-            self.num_entries.insert(caller, &0);
-            Ok(())
+            // removing a member rotates the key epoch
+            keyvault.remove_member(team_id, accounts.charlie).unwrap();
+            assert_eq!(keyvault.get_team(team_id).unwrap().key_epoch, 1);
+            assert_eq!(
+                keyvault.get_team_member(team_id, accounts.charlie),
+                Err(Error::NotTeamMember)
+            );
+            keyvault
+                .add_team_entry(team_id, 1, Vec::new(), Vec::from([2]), team_metadata(1, 0))
+                .unwrap();
+            let entries = keyvault.get_team_entries(team_id, 0, 2).unwrap();
+            assert_eq!(entries[0].author, accounts.charlie);
+            assert_eq!(entries[0].key_epoch, 0);
+            assert_eq!(entries[1].key_epoch, 1);
         }
 
-        /// Retrieves the contract owner's account ID.
-        #[ink(message)]
-        pub fn get_owner(&self) -> AccountId {
-            self.owner
+        #[ink::test]
+        fn team_policy_rejects_writes_that_fall_short() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let team_id = keyvault.create_team(Vec::from([1])).unwrap();
+            keyvault
+                .add_member(team_id, accounts.charlie, TeamRole::Writer, Vec::from([2]))
+                .unwrap();
+            let policy = TeamPolicy {
+                min_kdf_iterations: 600_000,
+                allowed_schemes: Vec::from([2]),
+                min_extension_version: SemVer {
+                    major: 0,
+                    minor: 2,
+                    patch: 0,
+                },
+            };
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                keyvault.set_team_policy(team_id, Some(policy.clone())),
+                Err(Error::InsufficientTeamRole)
+            );
+            set_caller(accounts.bob);
+            keyvault.set_team_policy(team_id, Some(policy.clone())).unwrap();
+            assert_eq!(keyvault.get_team_policy(team_id), Some(policy.clone()));
+
+            set_caller(accounts.charlie);
+            let mut metadata = team_metadata(1, 600_000);
+            metadata.extension_version = policy.min_extension_version;
+            assert_eq!(
+                keyvault.add_team_entry(team_id, 0, Vec::new(), Vec::new(), metadata),
+                Err(Error::DisallowedScheme)
+            );
+            metadata.scheme_id = 2;
+            metadata.kdf_iterations = 599_999;
+            assert_eq!(
+                keyvault.add_team_entry(team_id, 0, Vec::new(), Vec::new(), metadata),
+                Err(Error::WeakKdf)
+            );
+            metadata.kdf_iterations = 600_000;
+            metadata.extension_version = CLIENT_VERSION;
+            assert_eq!(
+                keyvault.add_team_entry(team_id, 0, Vec::new(), Vec::new(), metadata),
+                Err(Error::ClientTooOld)
+            );
+            metadata.extension_version = policy.min_extension_version;
+            keyvault
+                .add_team_entry(team_id, 0, Vec::new(), Vec::new(), metadata)
+                .unwrap();
+            // updates are held to the same policy
+            assert_eq!(
+                keyvault.update_team_entry(team_id, 0, Vec::new(), Vec::new(), team_metadata(2, 1)),
+                Err(Error::WeakKdf)
+            );
+
+            set_caller(accounts.bob);
+            keyvault.set_team_policy(team_id, None).unwrap();
+            set_caller(accounts.charlie);
+            keyvault
+                .update_team_entry(team_id, 0, Vec::new(), Vec::new(), team_metadata(1, 1))
+                .unwrap();
         }
 
-        /// Retrieves the contract owner's account ID.
-        #[ink(message)]
-        pub fn get_owner_result(&self) -> Result<AccountId> {
-            Ok(self.owner)
+        #[ink::test]
+        fn paused_contract_rejects_writes_until_unpaused() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            assert_eq!(keyvault.pause(String::from("incident")), Err(Error::MissingRole));
+            set_caller(accounts.alice);
+            keyvault
+                .grant_role(Role::Pauser, accounts.charlie)
+                .unwrap();
+            set_caller(accounts.charlie);
+            keyvault.pause(String::from("incident")).unwrap();
+            assert_eq!(keyvault.get_pause_reason(), Some(String::from("incident")));
+
+            set_caller(accounts.bob);
+            assert_eq!(add_entry(&mut keyvault, 0), Err(Error::Paused));
+            set_caller(accounts.django);
+            assert_eq!(
+                keyvault.create_account(Vec::from([1]), None, CLIENT_VERSION),
+                Err(Error::Paused)
+            );
+            // reads keep working
+            assert_eq!(keyvault.get_entry_count(accounts.bob), Ok(0));
+
+            set_caller(accounts.charlie);
+            keyvault.unpause().unwrap();
+            assert_eq!(keyvault.unpause(), Err(Error::NotPaused));
+            set_caller(accounts.bob);
+            assert_eq!(add_entry(&mut keyvault, 0), Ok(()));
         }
 
-        /// Retrieves the latest versions for both the KeyVault smart contract and browser extension.
-        ///
-        ///   returns (
-        ///     current smart contract version,
-        ///     latest smart contract version,
-        ///     latest smart contract address,
-        ///     latest compatible browser extension version,
-        ///   )
-        #[ink(message)]
-        pub fn get_versions(&self) -> (u8, u8, AccountId, u32) {
-            (
-                VERSION,
-                self.latest_smart_contract_version,
-                self.latest_smart_contract_address
-                    .unwrap_or_else(|| self.env().account_id()),
-                self.latest_compatible_browser_extension_version,
-            )
+        #[ink::test]
+        fn sunset_contract_rejects_writes_but_releases_org_funds() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let contract = ink::env::account_id::<Env>();
+            ink::env::test::set_account_balance::<Env>(contract, 100);
+
+            set_caller(accounts.charlie);
+            ink::env::test::set_value_transferred::<Env>(100);
+            let org_id = keyvault.create_org(1, 0).unwrap();
+            ink::env::test::set_value_transferred::<Env>(0);
+
+            let deadline = 1 + DEFAULT_ADMIN_DELAY + 10;
+            set_caller(accounts.alice);
+            let sunset_id = keyvault.set_sunset_deadline(Some(deadline)).unwrap();
+            let successor_id = keyvault
+                .set_latest_smart_contract_address(accounts.frank)
+                .unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(sunset_id).unwrap();
+
+            set_caller(accounts.bob);
+            assert_eq!(add_entry(&mut keyvault, 0), Ok(()));
+
+            set_time(deadline);
+            assert_eq!(add_entry(&mut keyvault, 1), Err(Error::Sunset(None)));
+            set_caller(accounts.alice);
+            keyvault.execute_admin_action(successor_id).unwrap();
+            set_caller(accounts.bob);
+            assert_eq!(
+                add_entry(&mut keyvault, 1),
+                Err(Error::Sunset(Some(accounts.frank)))
+            );
+            assert_eq!(keyvault.get_entry_count(accounts.bob), Ok(1));
+
+            // prepaid organization funds can still be withdrawn
+            set_caller(accounts.charlie);
+            keyvault.withdraw_org_balance(org_id, 60).unwrap();
+            assert_eq!(keyvault.org_balance(org_id), Ok(40));
+            assert_eq!(ink::env::test::get_account_balance::<Env>(contract), Ok(40));
         }
 
-        /// Updates the latest KeyVault smart contract version, owner-restricted.
-        #[ink(message)]
-        pub fn set_latest_smart_contract_version(&mut self, latest: u8) -> Result<()> {
-            self.is_owner()?;
-            self.latest_smart_contract_version = latest;
-            Ok(())
+        #[ink::test]
+        fn only_the_predecessor_imports_vaults_in_order() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+            let entry = EncryptedEntry {
+                iv: Vec::from([0; 12]),
+                ciphertext: Vec::from([1, 2, 3]),
+                device_id: None,
+                shared_by: None,
+            };
+
+            assert_eq!(keyvault.migrate_to_latest(0, 10), Err(Error::NoSuccessor));
+
+            set_caller(accounts.frank);
+            assert_eq!(
+                keyvault.import_from_previous(accounts.django, Vec::from([4]), 0, Vec::new()),
+                Err(Error::NotPredecessor)
+            );
+
+            set_caller(accounts.alice);
+            let action_id = keyvault.set_predecessor(Some(accounts.frank)).unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+
+            set_caller(accounts.frank);
+            assert_eq!(
+                keyvault.import_from_previous(
+                    accounts.django,
+                    Vec::from([4]),
+                    1,
+                    Vec::from([entry.clone()])
+                ),
+                Err(Error::AccountNotFound)
+            );
+            keyvault
+                .import_from_previous(
+                    accounts.django,
+                    Vec::from([4]),
+                    0,
+                    Vec::from([entry.clone(), entry.clone()]),
+                )
+                .unwrap();
+            assert_eq!(
+                keyvault.import_from_previous(
+                    accounts.django,
+                    Vec::from([4]),
+                    0,
+                    Vec::from([entry.clone()])
+                ),
+                Err(Error::IndexMismatch)
+            );
+            keyvault
+                .import_from_previous(
+                    accounts.django,
+                    Vec::from([5]),
+                    2,
+                    Vec::from([entry.clone()]),
+                )
+                .unwrap();
+
+            assert_eq!(keyvault.get_entry_count(accounts.django), Ok(3));
+            // the encryption key hash only comes from the first batch
+            assert_eq!(
+                keyvault.get_encryption_key_hash(accounts.django),
+                Ok(Vec::from([4]))
+            );
+            assert_eq!(keyvault.get_entry(accounts.django, 2), Ok(entry));
         }
 
-        /// Updates the latest browser extension version, owner-restricted.
-        #[ink(message)]
-        pub fn set_latest_compatible_browser_extension_version(
-            &mut self,
-            latest: u32,
-        ) -> Result<()> {
-            self.is_owner()?;
-            self.latest_compatible_browser_extension_version = latest;
-            Ok(())
+        #[ink::test]
+        fn admin_actions_wait_for_admin_delay() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            set_caller(accounts.bob);
+            assert_eq!(keyvault.set_admin_delay(0), Err(Error::NotOwner));
+
+            set_caller(accounts.alice);
+            let action_id = keyvault.set_admin_delay(0).unwrap();
+            assert_eq!(
+                keyvault.execute_admin_action(action_id),
+                Err(Error::AdminActionPending)
+            );
+
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+            assert_eq!(keyvault.get_admin_delay(), 0);
+            assert_eq!(
+                keyvault.execute_admin_action(action_id),
+                Err(Error::AdminActionNotFound)
+            );
         }
 
-        /// Updates the KeyVault smart contract owner, owner-restricted.
-        #[ink(message)]
-        pub fn set_owner(&mut self, new_owner: AccountId) -> Result<()> {
-            self.is_owner()?;
-            self.owner = new_owner;
-            Ok(())
+        #[ink::test]
+        fn admin_delay_is_capped() {
+            let mut keyvault = setup();
+            set_caller(accounts().alice);
+
+            assert_eq!(
+                keyvault.set_admin_delay(Timestamp::MAX),
+                Err(Error::AdminDelayTooLong)
+            );
+            let action_id = keyvault.set_admin_delay(MAX_ADMIN_DELAY).unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+
+            // the delay can still be lowered again, just after the longest delay
+            let action_id = keyvault.set_admin_delay(DEFAULT_ADMIN_DELAY).unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY + MAX_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+            assert_eq!(keyvault.get_admin_delay(), DEFAULT_ADMIN_DELAY);
         }
 
-        /// Updates account creation fee, owner-restricted.
-        #[ink(message)]
-        pub fn set_account_creation_fee(&mut self, new_fee: Balance) -> Result<()> {
-            self.is_owner()?;
-            self.fee = new_fee;
-            Ok(())
+        #[ink::test]
+        fn cancelled_admin_action_cannot_be_executed() {
+            let mut keyvault = setup();
+            set_caller(accounts().alice);
+
+            let action_id = keyvault.set_admin_delay(0).unwrap();
+            keyvault.cancel_admin_action(action_id).unwrap();
+
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            assert_eq!(
+                keyvault.execute_admin_action(action_id),
+                Err(Error::AdminActionNotFound)
+            );
+            assert_eq!(keyvault.get_admin_delay(), DEFAULT_ADMIN_DELAY);
         }
 
-        /// Gets the smart contract's balance.
-        #[ink(message)]
-        pub fn get_balance(&self) -> Balance {
-            self.env().balance()
+        #[ink::test]
+        fn roles_follow_ownership_to_new_owner() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            set_caller(accounts.alice);
+            let action_id = keyvault.propose_owner(accounts.charlie).unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+            assert_eq!(keyvault.get_owner(), accounts.alice);
+
+            set_caller(accounts.bob);
+            assert_eq!(keyvault.accept_ownership(), Err(Error::NotPendingOwner));
+
+            set_caller(accounts.charlie);
+            keyvault.accept_ownership().unwrap();
+            assert_eq!(keyvault.get_owner(), accounts.charlie);
+            assert_eq!(keyvault.get_pending_owner(), None);
+            for role in Role::ALL {
+                assert!(keyvault.has_role(role, accounts.charlie));
+                assert!(!keyvault.has_role(role, accounts.alice));
+            }
+
+            set_caller(accounts.alice);
+            assert_eq!(keyvault.pause(String::from("test")), Err(Error::MissingRole));
         }
 
-        /// Withdraws the smart contract's balance
-        #[ink(message)]
-        pub fn withdraw(&mut self) -> Result<()> {
-            self.is_owner()?;
+        #[ink::test]
+        fn renouncing_ownership_revokes_owner_roles_only() {
+            let mut keyvault = setup();
+            let accounts = accounts();
 
-            let balance = self.env().balance();
-            if balance > 0 {
-                self.env()
-                    .transfer(self.owner, balance)
-                    .map_err(|_| Error::TransferFailed)?;
+            set_caller(accounts.alice);
+            keyvault.grant_role(Role::Pauser, accounts.django).unwrap();
+            let action_id = keyvault.renounce_ownership().unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(action_id).unwrap();
+
+            assert_eq!(keyvault.get_owner(), AccountId::from([0; 32]));
+            for role in Role::ALL {
+                assert!(!keyvault.has_role(role, accounts.alice));
             }
-            Ok(())
+            assert!(keyvault.has_role(Role::Pauser, accounts.django));
+        }
+
+        #[ink::test]
+        fn renouncing_ownership_cancels_queued_owner_actions() {
+            let mut keyvault = setup();
+            let accounts = accounts();
+
+            set_caller(accounts.alice);
+            let propose_id = keyvault.propose_owner(accounts.eve).unwrap();
+            let delay_id = keyvault.set_admin_delay(0).unwrap();
+            let fee_id = keyvault.set_account_creation_fee(1).unwrap();
+            let renounce_id = keyvault.renounce_ownership().unwrap();
+            set_time(1 + DEFAULT_ADMIN_DELAY);
+            keyvault.execute_admin_action(renounce_id).unwrap();
+
+            set_caller(accounts.eve);
+            assert_eq!(
+                keyvault.execute_admin_action(propose_id),
+                Err(Error::AdminActionNotFound)
+            );
+            assert_eq!(
+                keyvault.execute_admin_action(delay_id),
+                Err(Error::AdminActionNotFound)
+            );
+            assert_eq!(keyvault.accept_ownership(), Err(Error::NotPendingOwner));
+            assert!(!keyvault.has_role(Role::Treasurer, accounts.eve));
+
+            // actions gated by roles other accounts may still hold are left alone
+            assert_eq!(keyvault.execute_admin_action(fee_id), Ok(()));
         }
     }
 }
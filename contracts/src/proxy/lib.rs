@@ -0,0 +1,57 @@
+//! A forwarding proxy that keeps one stable address across KeyVault upgrades.
+//!
+//! Every call is delegated to the KeyVault code hash stored under `TARGET_KEY`, so the KeyVault
+//! code runs against this contract's storage. The proxy is installed onto an already deployed
+//! KeyVault by queueing its `install_proxy` with the proxy's code hash. From then on the
+//! KeyVault's admin timelock governs every change of target through `set_proxy_target`, while the
+//! address clients use never changes.
+//!
+//! The entry points are written by hand instead of with `#[ink::contract]`: ink!'s dispatcher
+//! decodes the contract's root storage before every message, and here that storage is the
+//! KeyVault's, not the proxy's. Being code-only, the proxy is built with
+//! `cargo contract build --generate code-only` and uploaded rather than instantiated.
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+use ink::env::call::build_call;
+use ink::env::{CallFlags, DefaultEnvironment};
+use ink::primitives::Hash;
+
+/// Storage key holding the KeyVault code hash calls are delegated to.
+///
+/// Must match `PROXY_TARGET_KEY` in the KeyVault contract, which writes it.
+pub const TARGET_KEY: u32 = 0x6b76_7078;
+
+/// Always reverts, since a fresh proxy has no KeyVault storage to forward calls to.
+#[cfg(not(feature = "std"))]
+#[no_mangle]
+pub extern "C" fn deploy() {
+    ink::env::return_value(ink::env::ReturnFlags::new_with_reverted(true), &());
+}
+
+/// Forwards every call to the target KeyVault code.
+#[cfg(not(feature = "std"))]
+#[no_mangle]
+pub extern "C" fn call() {
+    forward();
+}
+
+/// Delegates the current call, input and transferred value included, to the target KeyVault
+/// code, which replies to the caller directly.
+pub fn forward() -> ! {
+    let target = ink::env::get_contract_storage::<u32, Hash>(&TARGET_KEY)
+        .ok()
+        .flatten()
+        .expect("Proxy target is not set.");
+    build_call::<DefaultEnvironment>()
+        .delegate(target)
+        .call_flags(
+            CallFlags::default()
+                .set_forward_input(true)
+                .set_tail_call(true),
+        )
+        .try_invoke()
+        .expect("Delegating to the proxy target failed.")
+        .expect("Proxy target could not dispatch the call.");
+    unreachable!("tail calls never return to the proxy");
+}
@@ -0,0 +1,404 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod registry {
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    /// Time (in milliseconds) between registering a release and it becoming current, giving
+    /// users warning before KeyVault contracts point them to a new address.
+    const ACTIVATION_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+
+    /// Defines an event that is emitted
+    /// when the owner registers a new KeyVault release.
+    #[ink(event)]
+    pub struct RegisteredRelease {
+        version: u8,
+        code_hash: Hash,
+        address: AccountId,
+        activates_at: Timestamp,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner changes the status of a KeyVault release.
+    #[ink(event)]
+    pub struct UpdatedReleaseStatus {
+        version: u8,
+        status: ReleaseStatus,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner proposes a new registry owner.
+    #[ink(event)]
+    pub struct ProposedOwner {
+        owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when the owner cancels a proposed ownership transfer.
+    #[ink(event)]
+    pub struct CancelledOwnershipTransfer {
+        owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    /// Defines an event that is emitted
+    /// when a proposed owner accepts ownership of the registry.
+    #[ink(event)]
+    pub struct TransferredOwnership {
+        previous_owner: AccountId,
+        new_owner: AccountId,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Error for when a non-owner attempts a restricted operation.
+        NotOwner,
+        /// Error for when someone other than the proposed owner attempts to accept ownership.
+        NotPendingOwner,
+        /// Error for when no ownership transfer has been proposed.
+        OwnershipTransferNotFound,
+        /// Error for when a release with the same version is already registered.
+        ReleaseAlreadyExists,
+        /// Error for operations on a version that was never registered.
+        ReleaseNotFound,
+        /// Error for when a release's status would move backwards, e.g. from retired to active.
+        InvalidStatusChange,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Lifecycle of a KeyVault release.
+    ///
+    /// Releases only move forward: active, then deprecated, then retired.
+    #[derive(Debug, Clone, Copy, scale::Encode, scale::Decode, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ReleaseStatus {
+        /// Clients should use this release.
+        Active,
+        /// Still accepts writes, but clients should move to a newer release.
+        Deprecated,
+        /// No longer in use; clients should only read from it to move their vaults.
+        Retired,
+    }
+
+    /// A deployed KeyVault release.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Release {
+        /// Version of the KeyVault smart contract, as returned by its `get_versions`.
+        version: u8,
+        /// Hash of the release's contract code.
+        code_hash: Hash,
+        /// Address the release is deployed at.
+        address: AccountId,
+        /// Where the release is in its lifecycle.
+        status: ReleaseStatus,
+        /// When the release was registered.
+        registered_at: Timestamp,
+        /// When the release may become current, `ACTIVATION_DELAY` after it was registered.
+        activates_at: Timestamp,
+    }
+
+    #[ink(storage)]
+    pub struct Registry {
+        /// Owner's account ID.
+        ///
+        /// The owner has exclusive rights to register releases and change their status.
+        owner: AccountId,
+
+        /// Account proposed as the next owner, pending its acceptance.
+        ///
+        /// Ownership only changes once this account accepts, so a typo can't lock the registry.
+        pending_owner: Option<AccountId>,
+
+        /// Mapping of each version to its KeyVault release.
+        releases: Mapping<u8, Release>,
+
+        /// Registered versions, in the order they were registered.
+        versions: Vec<u8>,
+    }
+
+    impl Registry {
+        #[ink(constructor)]
+        pub fn new(owner: AccountId) -> Self {
+            Self {
+                owner,
+                pending_owner: None,
+                releases: Mapping::new(),
+                versions: Vec::new(),
+            }
+        }
+
+        /// Restricts a transaction to the contract owner.
+        fn is_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        /// Registers a new, active KeyVault release, owner-restricted.
+        ///
+        /// The release only becomes current once `ACTIVATION_DELAY` has elapsed.
+        #[ink(message)]
+        pub fn register_release(
+            &mut self,
+            version: u8,
+            code_hash: Hash,
+            address: AccountId,
+        ) -> Result<()> {
+            self.is_owner()?;
+            if self.releases.contains(version) {
+                return Err(Error::ReleaseAlreadyExists);
+            }
+
+            let registered_at = self.env().block_timestamp();
+            let activates_at = registered_at
+                .checked_add(ACTIVATION_DELAY)
+                .expect("Overflow occurred.");
+            self.releases.insert(
+                version,
+                &Release {
+                    version,
+                    code_hash,
+                    address,
+                    status: ReleaseStatus::Active,
+                    registered_at,
+                    activates_at,
+                },
+            );
+            self.versions.push(version);
+
+            // emit event
+            Self::env().emit_event(RegisteredRelease {
+                version,
+                code_hash,
+                address,
+                activates_at,
+            });
+            Ok(())
+        }
+
+        /// Moves a release forward in its lifecycle, owner-restricted.
+        #[ink(message)]
+        pub fn set_release_status(&mut self, version: u8, status: ReleaseStatus) -> Result<()> {
+            self.is_owner()?;
+            let mut release = self.releases.get(version).ok_or(Error::ReleaseNotFound)?;
+            if status <= release.status {
+                return Err(Error::InvalidStatusChange);
+            }
+
+            release.status = status;
+            self.releases.insert(version, &release);
+
+            // emit event
+            Self::env().emit_event(UpdatedReleaseStatus { version, status });
+            Ok(())
+        }
+
+        /// Proposes `new_owner` as the next registry owner, owner-restricted.
+        ///
+        /// The proposed owner must call `accept_ownership` for the transfer to take effect.
+        #[ink(message)]
+        pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<()> {
+            self.is_owner()?;
+            self.pending_owner = Some(new_owner);
+
+            // emit event
+            Self::env().emit_event(ProposedOwner {
+                owner: self.owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Accepts ownership of the registry, restricted to the proposed owner.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::NotPendingOwner);
+            }
+
+            let previous_owner = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+
+            // emit event
+            Self::env().emit_event(TransferredOwnership {
+                previous_owner,
+                new_owner: caller,
+            });
+            Ok(())
+        }
+
+        /// Cancels a proposed ownership transfer, owner-restricted.
+        #[ink(message)]
+        pub fn cancel_ownership_transfer(&mut self) -> Result<()> {
+            self.is_owner()?;
+            let new_owner = self
+                .pending_owner
+                .take()
+                .ok_or(Error::OwnershipTransferNotFound)?;
+
+            // emit event
+            Self::env().emit_event(CancelledOwnershipTransfer {
+                owner: self.owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Retrieves the owner's account ID.
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Retrieves the proposed next owner, if an ownership transfer is pending.
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Retrieves the release registered for `version`, if any.
+        #[ink(message)]
+        pub fn get_release(&self, version: u8) -> Option<Release> {
+            self.releases.get(version)
+        }
+
+        /// Retrieves all registered releases, in the order they were registered.
+        #[ink(message)]
+        pub fn get_releases(&self) -> Vec<Release> {
+            self.versions
+                .iter()
+                .filter_map(|version| self.releases.get(version))
+                .collect()
+        }
+
+        /// Retrieves the version and address of the current release, i.e. the active release
+        /// with the highest version whose activation delay has elapsed.
+        ///
+        /// Moving the current release out of active status falls back to the previous one at
+        /// once, but that release went through the activation delay itself.
+        ///
+        /// Kept to plain types so KeyVault contracts can query it without depending on this crate.
+        #[ink(message)]
+        pub fn get_current_release(&self) -> Option<(u8, AccountId)> {
+            self.versions
+                .iter()
+                .filter_map(|version| self.releases.get(version))
+                .filter(|release| release.status == ReleaseStatus::Active)
+                .filter(|release| release.activates_at <= self.env().block_timestamp())
+                .max_by_key(|release| release.version)
+                .map(|release| (release.version, release.address))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        type Env = ink::env::DefaultEnvironment;
+
+        fn accounts() -> ink::env::test::DefaultAccounts<Env> {
+            ink::env::test::default_accounts::<Env>()
+        }
+
+        fn set_caller(account: AccountId) {
+            ink::env::test::set_caller::<Env>(account);
+        }
+
+        fn set_time(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<Env>(timestamp);
+        }
+
+        /// Deploys a registry owned by Alice.
+        fn setup() -> Registry {
+            set_time(1);
+            set_caller(accounts().alice);
+            Registry::new(accounts().alice)
+        }
+
+        #[ink::test]
+        fn releases_become_current_after_activation_delay() {
+            let mut registry = setup();
+            let accounts = accounts();
+
+            registry
+                .register_release(1, Hash::from([1; 32]), accounts.charlie)
+                .unwrap();
+            assert_eq!(registry.get_current_release(), None);
+
+            set_time(1 + ACTIVATION_DELAY);
+            assert_eq!(registry.get_current_release(), Some((1, accounts.charlie)));
+
+            registry
+                .register_release(2, Hash::from([2; 32]), accounts.django)
+                .unwrap();
+            assert_eq!(registry.get_current_release(), Some((1, accounts.charlie)));
+
+            set_time(1 + 2 * ACTIVATION_DELAY);
+            assert_eq!(registry.get_current_release(), Some((2, accounts.django)));
+
+            registry
+                .set_release_status(2, ReleaseStatus::Deprecated)
+                .unwrap();
+            assert_eq!(registry.get_current_release(), Some((1, accounts.charlie)));
+        }
+
+        #[ink::test]
+        fn releases_are_owner_restricted_and_only_move_forward() {
+            let mut registry = setup();
+            let accounts = accounts();
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                registry.register_release(1, Hash::from([1; 32]), accounts.charlie),
+                Err(Error::NotOwner)
+            );
+
+            set_caller(accounts.alice);
+            registry
+                .register_release(1, Hash::from([1; 32]), accounts.charlie)
+                .unwrap();
+            assert_eq!(
+                registry.register_release(1, Hash::from([1; 32]), accounts.django),
+                Err(Error::ReleaseAlreadyExists)
+            );
+            registry
+                .set_release_status(1, ReleaseStatus::Retired)
+                .unwrap();
+            assert_eq!(
+                registry.set_release_status(1, ReleaseStatus::Active),
+                Err(Error::InvalidStatusChange)
+            );
+        }
+
+        #[ink::test]
+        fn ownership_moves_only_once_accepted() {
+            let mut registry = setup();
+            let accounts = accounts();
+
+            registry.propose_owner(accounts.bob).unwrap();
+            assert_eq!(registry.get_owner(), accounts.alice);
+
+            set_caller(accounts.charlie);
+            assert_eq!(registry.accept_ownership(), Err(Error::NotPendingOwner));
+
+            set_caller(accounts.bob);
+            registry.accept_ownership().unwrap();
+            assert_eq!(registry.get_owner(), accounts.bob);
+            assert_eq!(registry.get_pending_owner(), None);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 import { AccountName, ConnectButton } from '@/components/web3/ConnectButton'
+import { CLIENT_VERSION } from '@/config/clientVersion'
 import { ContractIds } from '@/deployments/deployments'
 import { contractTxWithToast } from '@/utils/contractTxWithToast'
 import { truncateHash } from '@/utils/truncateHash'
@@ -42,6 +43,9 @@ export const AccountCreation = ({ context, postMessage }: AccountCreationPropsTy
     try {
       await contractTxWithToast(api, activeAccount.address, contract, 'createAccount', {}, [
         context.encryptionKeyHash,
+        // the extension may hand over the hash of a recovery code to set up with the account
+        context.recoveryCodeHash ?? null,
+        CLIENT_VERSION,
       ])
       // `postMessage` will not be executed until after `await contractTxWithToast` is done
       postMessage('TO_EXTENSION', 'ACCOUNT_CREATION_SUCCESS', {})
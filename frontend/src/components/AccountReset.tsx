@@ -1,11 +1,22 @@
 import { AccountName, ConnectButton } from '@/components/web3/ConnectButton'
+import { CLIENT_VERSION } from '@/config/clientVersion'
 import { ContractIds } from '@/deployments/deployments'
 import { contractTxWithToast } from '@/utils/contractTxWithToast'
 import { truncateHash } from '@/utils/truncateHash'
 import { Box, Button, FormControl, FormLabel, HStack, Heading, Text } from '@chakra-ui/react'
 import { encodeAddress } from '@polkadot/util-crypto'
-import { useInkathon, useRegisteredContract } from '@scio-labs/use-inkathon'
-import { useState } from 'react'
+import {
+  contractQuery,
+  decodeOutput,
+  useInkathon,
+  useRegisteredContract,
+} from '@scio-labs/use-inkathon'
+import { useEffect, useState } from 'react'
+
+type PendingReset = {
+  encryptionKeyHash: string
+  executableAt: number
+}
 
 type AccountResetPropsType = {
   context: Record<string, any>
@@ -18,6 +29,48 @@ export const AccountReset = ({ context, postMessage }: AccountResetPropsType) =>
   const [isLoading, setIsLoading] = useState(false)
   const { contract } = useRegisteredContract(ContractIds.KeyVault)
   const [accountReset, setAccountReset] = useState<boolean>(false)
+  const [pendingReset, setPendingReset] = useState<PendingReset | null>(null)
+
+  // resets wait for the account's reset delay, so look up any reset already requested
+  const getPendingReset = async (): Promise<PendingReset | null> => {
+    if (!activeAccount || !contract || !api) return null
+
+    const result = await contractQuery(api, '', contract, 'get_pending_reset', {}, [
+      activeAccount.address,
+    ])
+    const { output, isError } = decodeOutput(result, contract, 'get_pending_reset')
+    const pending =
+      isError || !output
+        ? null
+        : {
+            encryptionKeyHash: output.encryptionKeyHash,
+            // decoded numbers come formatted with thousands separators
+            executableAt: Number(String(output.executableAt).replace(/,/g, '')),
+          }
+    setPendingReset(pending)
+    return pending
+  }
+
+  useEffect(() => {
+    getPendingReset()
+  }, [api, contract, activeAccount?.address])
+
+  const handleCancel = async (e: React.MouseEvent<HTMLButtonElement>) => {
+    e.preventDefault()
+    if (!activeAccount || !contract || !activeSigner || !api) {
+      return
+    }
+
+    setIsLoading(true)
+    try {
+      await contractTxWithToast(api, activeAccount.address, contract, 'cancelReset', {}, [])
+    } catch (e: unknown) {
+      console.error(e)
+    } finally {
+      await getPendingReset()
+      setIsLoading(false)
+    }
+  }
 
   const handleSubmit = async (e: React.MouseEvent<HTMLButtonElement>) => {
     e.preventDefault()
@@ -29,9 +82,19 @@ export const AccountReset = ({ context, postMessage }: AccountResetPropsType) =>
 
     let errored = false // used to emulate python's `else` part of try-except-else
     try {
-      await contractTxWithToast(api, activeAccount.address, contract, 'resetAccount', {}, [
-        context.encryptionKeyHash,
-      ])
+      if (pendingReset) {
+        await contractTxWithToast(api, activeAccount.address, contract, 'executeReset', {}, [])
+      } else {
+        await contractTxWithToast(api, activeAccount.address, contract, 'requestReset', {}, [
+          context.encryptionKeyHash,
+          CLIENT_VERSION,
+        ])
+        // accounts that opted into instant resets are reset right away, others wait
+        if (await getPendingReset()) {
+          errored = true // not reset yet, so don't report success
+          return
+        }
+      }
       // `postMessage` will not be executed until after `await contractTxWithToast` is done
       postMessage('TO_EXTENSION', 'ACCOUNT_RESET_SUCCESS', {})
     } catch (e: unknown) {
@@ -98,6 +161,15 @@ export const AccountReset = ({ context, postMessage }: AccountResetPropsType) =>
             )}
           </FormControl>
 
+          {/* Pending reset */}
+          {pendingReset && (
+            <Text textColor={'gray.200'} opacity={0.75}>
+              Your reset was requested and can be executed from{' '}
+              {new Date(pendingReset.executableAt).toLocaleString()} on. If you did not
+              request it, cancel it now.
+            </Text>
+          )}
+
           {/* Submission */}
           <Button
             width="full"
@@ -106,12 +178,27 @@ export const AccountReset = ({ context, postMessage }: AccountResetPropsType) =>
             type="submit"
             colorScheme="purple"
             fontSize={'xl'}
-            isDisabled={!connected}
+            isDisabled={!connected || (!!pendingReset && Date.now() < pendingReset.executableAt)}
             isLoading={isLoading}
             onClick={handleSubmit}
           >
-            Reset Account
+            {pendingReset ? 'Execute Reset' : 'Request Reset'}
           </Button>
+          {pendingReset && (
+            <Button
+              width="full"
+              py={4}
+              mb={8}
+              variant="outline"
+              colorScheme="purple"
+              fontSize={'xl'}
+              isDisabled={!connected}
+              isLoading={isLoading}
+              onClick={handleCancel}
+            >
+              Cancel Reset
+            </Button>
+          )}
         </form>
       </Box>
     </>
@@ -0,0 +1,5 @@
+/**
+ * Client version sent along with account writes, checked against the contract's
+ * extension compatibility. Keep in sync with `version` in `package.json`.
+ */
+export const CLIENT_VERSION = { major: 0, minor: 1, patch: 3 }
@@ -1,4 +1,5 @@
 import { CustomButton } from '@/components/CustomButton'
+import { CLIENT_VERSION } from '@/config/clientVersion'
 import { ContractIds } from '@/deployments/deployments'
 import { usePostMessages } from '@/hooks/usePostMessages'
 import { contractTxWithToast } from '@/utils/contractTxWithToast'
@@ -95,6 +96,8 @@ const HomePage: NextPage = () => {
       await contractTxWithToast(api, activeAccount.address, contract, 'addEntries', {}, [
         numOnChain,
         encrypted.slice(numOnChain),
+        null,
+        CLIENT_VERSION,
       ])
       postMessage('TO_EXTENSION', 'SYNC_SUCCESS', { numOnChain: encrypted.length })
       setState('success')